@@ -0,0 +1,225 @@
+//! Generate TypeScript bindings during `cargo build` instead of `cargo test`.
+//!
+//! When a crate has a build script, the `#[command]` macro writes a descriptor for each binding to `OUT_DIR`.
+//! Calling [`generate_all`] from the build script turns these descriptors into TypeScript files:
+//!
+//! ```no_run
+//! // in build.rs, after tauri_build::build()
+//! tauri_bindgen_ts::build::generate_all();
+//! ```
+//!
+//! **Bindings lag one compilation behind:** the build script runs before the crate is compiled, so it writes the bindings described by the previous compilation.
+//! A fresh `cargo build`, i.e. in CI, writes none at all, and changes to a command are only written by the build after the one that compiled them.
+//! Run `cargo build` twice or generate the bindings with `cargo test` where they need to be up to date.
+//! Descriptors that the previous compilation did not write, i.e. of removed or renamed commands, are deleted instead of being written again.
+//! Entity types are exported by ts_rs and still require running `cargo test`.
+//! Bindings of commands behind `#[cfg(...)]` are only written if the predicate holds for the features and target with which the build script runs.
+//! Breaking changes of existing bindings are reported as build warnings, see [`export::DENY_BREAKING_VAR`](crate::export::DENY_BREAKING_VAR) to fail the build instead.
+//! With [`export::DRY_RUN_VAR`](crate::export::DRY_RUN_VAR) set, nothing is written and the bindings are printed to the output of the build script in the `target` directory.
+
+use std::{env, fs, path::{Path, PathBuf}};
+
+use serde::Deserialize;
+
+use crate::export;
+
+/// Name of the directory inside `OUT_DIR` that holds the descriptors
+pub(crate) const DESCRIPTOR_DIR: &str = "tauri-bindgen-ts";
+
+/// Extension of the files that list the descriptors written by a compilation, which are named such that the latest one sorts last
+const MANIFEST_EXTENSION: &str = "manifest";
+
+/// Subdirectory of the descriptors of entities, named by their Rust type
+const ENTITY_DIR: &str = "entities";
+
+/// Encloses the Rust name of an entity in the content of a descriptor, which is replaced with its TypeScript name
+const ENTITY_NAME: char = '\u{1}';
+
+/// Encloses the Rust name of an entity in an import path, which is replaced with the path from which the entity is imported
+const ENTITY_IMPORT: char = '\u{2}';
+
+/// A binding that was described by a macro during compilation
+#[derive(Deserialize)]
+pub(crate) struct Descriptor {
+    /// Path of the generated file, relative to the crate root
+    pub(crate) file: PathBuf,
+    pub(crate) content: String,
+    /// Module in which the binding is placed, if any
+    #[serde(default)]
+    pub(crate) module: Option<Module>,
+    /// Command that is listed in the index file of its export directory, which is missing for modules of commands
+    #[serde(default)]
+    pub(crate) command: Option<Command>,
+    /// Predicates of the ```#[cfg(...)]``` attributes of the command, which all need to hold for the binding to be generated
+    #[serde(default)]
+    pub(crate) cfg: Vec<Cfg>,
+    /// Formatter of the binding, which is "prettier" if configured with ```format = "prettier"```
+    #[serde(default)]
+    pub(crate) format: Option<String>,
+    /// Prefix of the import paths of entities without ```import_from```
+    #[serde(default)]
+    import_prefix: String,
+    /// Location of the annotated item, i.e. "src/lib.rs:12"
+    #[serde(default)]
+    pub(crate) source: Option<String>,
+}
+
+impl Descriptor {
+    /// Content of the binding along with the location of the annotated item, which descriptors of older versions of this crate do not specify
+    pub(crate) fn binding(&self) -> String {
+        match &self.source {
+            Some(source) => export::add_metadata(&self.content, source),
+            None => self.content.clone(),
+        }
+    }
+}
+
+/// TypeScript name of an entity and the path it specifies with ```import_from```
+#[derive(Deserialize)]
+struct Entity {
+    name: String,
+    import_from: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Module {
+    /// Export directory that contains the module, relative to the crate root
+    pub(crate) dir: PathBuf,
+    pub(crate) name: String,
+}
+
+/// Configuration predicate, which is evaluated with the environment variables that cargo sets for build scripts
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Cfg {
+    All(Vec<Cfg>),
+    Any(Vec<Cfg>),
+    Not(Box<Cfg>),
+    /// ```feature = "premium"```, ```target_os = "linux"``` or ```unix```
+    Option { key: String, value: Option<String> },
+}
+
+impl Cfg {
+    fn holds(&self) -> bool {
+        match self {
+            Cfg::All(predicates) => predicates.iter().all(Cfg::holds),
+            Cfg::Any(predicates) => predicates.iter().any(Cfg::holds),
+            Cfg::Not(predicate) => !predicate.holds(),
+            Cfg::Option { key, value: Some(feature) } if key == "feature" => env::var_os(format!("CARGO_FEATURE_{}", env_name(feature))).is_some(),
+            Cfg::Option { key, value: Some(value) } => env::var(format!("CARGO_CFG_{}", env_name(key))).is_ok_and(|values| values.split(',').any(|option| option == value)),
+            Cfg::Option { key, value: None } => env::var_os(format!("CARGO_CFG_{}", env_name(key))).is_some(),
+        }
+    }
+}
+
+/// Cargo passes features and cfg options to build scripts in uppercase, with dashes replaced by underscores
+fn env_name(name: &str) -> String {
+    name.to_uppercase().replace('-', "_")
+}
+
+#[derive(Deserialize)]
+pub(crate) struct Command {
+    /// Export directory whose index file lists the command, relative to the crate root
+    pub(crate) dir: PathBuf,
+    /// Name with which the command is invoked
+    pub(crate) name: String,
+}
+
+/// Writes all bindings that were described during the last compilation of this crate. Must be called from a build script.
+pub fn generate_all() {
+    let out_dir = env::var("OUT_DIR").expect("generate_all should only be called from a build script");
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("generate_all should only be called from a build script");
+
+    let dir = Path::new(&out_dir).join(DESCRIPTOR_DIR);
+    remove_outdated(&dir);
+
+    // Descriptors are written regardless of `cfg` attributes, so bindings of disabled commands are skipped here
+    for descriptor in descriptors(&dir).into_iter().filter(|descriptor| descriptor.cfg.iter().all(Cfg::holds)) {
+        let file = Path::new(&manifest_dir).join(&descriptor.file);
+        if descriptor.format.as_deref() == Some("prettier") {
+            export::use_prettier();
+        }
+        export::write_reporting(&file, &descriptor.binding(), |warning| println!("cargo:warning={warning}"));
+
+        if let (Some(dir), Some(name)) = (file.parent(), file.file_stem()) {
+            export::add_command_to_index(dir, &name.to_string_lossy());
+        }
+        if let Some(module) = descriptor.module {
+            export::add_module_to_index(Path::new(&manifest_dir).join(module.dir), &module.name);
+        }
+        if let Some(command) = descriptor.command {
+            export::add_command_to_list(Path::new(&manifest_dir).join(command.dir), &command.name);
+        }
+    }
+}
+
+fn descriptors(dir: &Path) -> Vec<Descriptor> {
+    descriptor_paths(dir).iter().map(|path| read_descriptor(path)).collect()
+}
+
+/// Paths of the descriptors in `dir` that were written by the latest compilation, sorted by name
+pub(crate) fn descriptor_paths(dir: &Path) -> Vec<PathBuf> {
+    let names = latest_manifest(dir).map(|(_, names)| names);
+
+    files(dir, "json").into_iter().filter(|path| names.as_ref().is_none_or(|names| is_listed(path, names))).collect()
+}
+
+/// Deletes the descriptors that are not listed in the latest manifest, along with all other manifests.
+/// This is only done by the build script of the crate itself, as the crate is never compiled while it runs.
+fn remove_outdated(dir: &Path) {
+    // Descriptors of older versions of this crate are not listed in a manifest and are kept
+    let Some((latest, names)) = latest_manifest(dir) else { return };
+
+    let outdated = files(dir, "json").into_iter().filter(|path| !is_listed(path, &names));
+    for path in outdated.chain(files(dir, MANIFEST_EXTENSION).into_iter().filter(|path| *path != latest)) {
+        let _ = fs::remove_file(path);
+    }
+}
+
+/// Path and listed descriptor names of the manifest of the latest compilation
+pub(crate) fn latest_manifest(dir: &Path) -> Option<(PathBuf, Vec<String>)> {
+    let path = files(dir, MANIFEST_EXTENSION).pop()?;
+    let names = fs::read_to_string(&path).ok()?.lines().map(str::to_owned).collect();
+
+    Some((path, names))
+}
+
+fn is_listed(path: &Path, names: &[String]) -> bool {
+    path.file_stem().is_some_and(|stem| names.iter().any(|name| stem.to_string_lossy() == *name))
+}
+
+/// Paths of all files in `dir` with the given extension, sorted by name
+fn files(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    // No descriptors have been written yet, i.e. on the first build
+    let Ok(entries) = fs::read_dir(dir) else { return vec![] };
+
+    let mut paths = entries.flatten().map(|entry| entry.path()).filter(|path| path.extension().is_some_and(|ext| ext == extension)).collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+pub(crate) fn read_descriptor(path: &Path) -> Descriptor {
+    let content = fs::read_to_string(path).expect("Could not read binding descriptor");
+    let mut descriptor: Descriptor = serde_json::from_str(&content).expect("Invalid binding descriptor");
+
+    let entities = path.parent().map(|dir| dir.join(ENTITY_DIR)).unwrap_or_default();
+    descriptor.content = resolve_entities(&descriptor.content, &entities, &descriptor.import_prefix);
+    descriptor
+}
+
+/// Replaces the Rust names of entities with the names and import paths that the tests use, which depend on their ```rename``` and ```import_from```.
+/// Entities of other crates are not described in `dir` and keep the name of their Rust type.
+fn resolve_entities(content: &str, dir: &Path, import_prefix: &str) -> String {
+    let entity = |name: &str| fs::read_to_string(dir.join(format!("{name}.json"))).ok().and_then(|entity| serde_json::from_str::<Entity>(&entity).ok());
+
+    let resolve = |content: &str, marker: char, replace: &dyn Fn(&str) -> String| {
+        content.split(marker).enumerate().map(|(index, part)| if index % 2 == 1 { replace(part) } else { part.to_owned() }).collect::<String>()
+    };
+    let content = resolve(content, ENTITY_NAME, &|name| entity(name).map_or_else(|| name.to_owned(), |entity| entity.name));
+
+    resolve(&content, ENTITY_IMPORT, &|name| match entity(name) {
+        Some(Entity { import_from: Some(path), .. }) => path,
+        Some(Entity { name, .. }) => format!("{import_prefix}{name}"),
+        None => format!("{import_prefix}{name}"),
+    })
+}
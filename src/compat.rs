@@ -0,0 +1,316 @@
+//! Detection of breaking changes between a previously generated binding and its new content.
+//!
+//! Instead of comparing the content as a whole, both versions are reduced to the members of their declarations,
+//! such as parameters of functions, fields of interfaces and the definition of type aliases.
+//! Declarations may span multiple lines, as is the case for bindings formatted with Prettier, and comments are skipped.
+
+use std::collections::BTreeMap;
+
+/// A parameter, field or type that is part of the contract between frontend and backend
+struct Member {
+    ty: String,
+    /// Type without whitespace and separators that formatters add or change, which is used for comparison
+    canonical: String,
+    optional: bool,
+    /// Whether the member is a function parameter, which breaks callers when it is added as required
+    is_param: bool,
+}
+
+/// Describes every change from `old` to `new` that could break code relying on the old binding
+pub(crate) fn breaking_changes(old: &str, new: &str) -> Vec<String> {
+    let old = fingerprint(old);
+    let new = fingerprint(new);
+
+    let mut changes = vec![];
+    for (key, before) in &old {
+        match new.get(key) {
+            None => changes.push(format!("`{key}` was removed")),
+            Some(after) if after.canonical != before.canonical => changes.push(format!("`{key}` changed from `{}` to `{}`", before.ty, after.ty)),
+            Some(after) if before.optional && !after.optional => changes.push(format!("`{key}` is no longer optional")),
+            Some(_) => {}
+        }
+    }
+    for (key, after) in &new {
+        if after.is_param && !after.optional && !old.contains_key(key) {
+            changes.push(format!("`{key}` was added as required parameter"));
+        }
+    }
+
+    changes
+}
+
+/// Members of all exported functions, interfaces and type aliases, keyed by their qualified name, i.e. `User.name` or `greet(user)`
+fn fingerprint(content: &str) -> BTreeMap<String, Member> {
+    let mut members = BTreeMap::new();
+    for decl in declarations(&strip_comments(content)) {
+        let Some(decl) = decl.strip_prefix("export ") else { continue };
+
+        if let Some(func) = decl.strip_prefix("async function ").or_else(|| decl.strip_prefix("function ")) {
+            function(func, &mut members);
+        } else if let Some(interface) = decl.strip_prefix("interface ") {
+            self::interface(interface, &mut members);
+        } else if let Some((name, ty)) = decl.strip_prefix("type ").and_then(|alias| alias.split_once(" = ")) {
+            let ty = ty.trim_end_matches(';').trim();
+            members.insert(name.to_owned(), Member::new(ty.strip_prefix("| ").unwrap_or(ty), false, false));
+        }
+    }
+
+    members
+}
+
+impl Member {
+    fn new(ty: &str, optional: bool, is_param: bool) -> Self {
+        Member { ty: ty.to_owned(), canonical: canonical(ty), optional, is_param }
+    }
+}
+
+/// Top-level statements starting at the beginning of a line, with their whitespace collapsed so that line breaks do not matter
+fn declarations(content: &str) -> Vec<String> {
+    let mut starts = vec![0];
+    let mut at_line_start = false;
+    scan(content, |index, c, depth| {
+        // Lines continuing a declaration, such as the closing bracket of an interface or members of a union, do not start a new one
+        if depth == 1 && at_line_start && !c.is_whitespace() && !matches!(c, ')' | ']' | '}' | '>' | '|' | '&' | '"' | '\'' | '`') {
+            starts.push(index);
+        }
+        match c {
+            '\n' => at_line_start = true,
+            c if !c.is_whitespace() => at_line_start = false,
+            _ => {}
+        }
+        None::<()>
+    });
+    starts.push(content.len());
+
+    starts.windows(2).map(|range| content[range[0]..range[1]].split_whitespace().collect::<Vec<_>>().join(" ")).filter(|decl| !decl.is_empty()).collect()
+}
+
+/// Removes line and block comments, such as the header and JSDoc of a binding
+fn strip_comments(content: &str) -> String {
+    let mut stripped = String::with_capacity(content.len());
+    let mut chars = content.chars().peekable();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        match (quote, c, chars.peek()) {
+            (Some(q), c, _) => {
+                if c == '\\' {
+                    stripped.push(c);
+                    stripped.extend(chars.next());
+                    continue;
+                }
+                if c == q {
+                    quote = None;
+                }
+            }
+            (None, '"' | '\'' | '`', _) => quote = Some(c),
+            (None, '/', Some('/')) => {
+                while chars.next_if(|c| *c != '\n').is_some() {}
+                continue;
+            }
+            (None, '/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    // Line breaks are kept, so that the next statement still starts at the beginning of a line
+                    if c == '\n' {
+                        stripped.push(c);
+                    }
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        stripped.push(c);
+    }
+
+    stripped
+}
+
+/// Whitespace, trailing separators and leading `|` of unions are added or removed by formatters, and `;` and `,` are interchangeable in object types
+fn canonical(ty: &str) -> String {
+    let mut canonical = String::with_capacity(ty.len());
+    let mut quote = None;
+    for c in ty.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, c) if c.is_whitespace() => continue,
+            (None, ';') => {
+                canonical.push(',');
+                continue;
+            }
+            (None, ')' | ']' | '}' | '>') if canonical.ends_with(',') => { canonical.pop(); }
+            _ => {}
+        }
+        canonical.push(c);
+    }
+
+    let canonical = canonical.strip_prefix('|').unwrap_or(&canonical);
+    canonical.replace("{|", "{").replace("(|", "(")
+}
+
+/// Parameters and return type of `name(params): Return { body }`
+fn function(func: &str, members: &mut BTreeMap<String, Member>) {
+    let Some((name, rest)) = func.split_once('(') else { return };
+    let Some(end) = closing(rest) else { return };
+    let (params, rest) = (&rest[..end], &rest[end + 1..]);
+
+    for (param, ty, optional) in split(params).iter().filter_map(|param| member(param)) {
+        members.insert(format!("{name}({param})"), Member::new(&ty, optional, true));
+    }
+    if let Some(ret) = rest.strip_prefix(": ").and_then(|rest| rest.split_once(" { ")).map(|(ret, _)| ret) {
+        members.insert(format!("{name}()"), Member::new(ret, false, false));
+    }
+}
+
+/// Fields of `Name { field: Type, }`
+fn interface(interface: &str, members: &mut BTreeMap<String, Member>) {
+    let Some((name, rest)) = interface.split_once(" {") else { return };
+    let Some(end) = closing(rest) else { return };
+
+    for (field, ty, optional) in split(&rest[..end]).iter().filter_map(|field| member(field)) {
+        members.insert(format!("{name}.{field}"), Member::new(&ty, optional, false));
+    }
+}
+
+/// Name, type and optionality of `readonly name?: Type`
+fn member(member: &str) -> Option<(String, String, bool)> {
+    let member = member.strip_prefix("readonly ").unwrap_or(member);
+    let (name, ty) = split_once(member, ':')?;
+    let (name, optional) = match name.trim().strip_suffix('?') {
+        Some(name) => (name, true),
+        None => (name.trim(), false),
+    };
+
+    Some((name.trim_matches('"').to_owned(), ty.trim().to_owned(), optional))
+}
+
+/// Index of the bracket that closes the one opened right before `rest`
+fn closing(rest: &str) -> Option<usize> {
+    scan(rest, |index, c, depth| (depth == 0 && matches!(c, ')' | '}')).then_some(index))
+}
+
+/// Splits at top-level commas and semicolons, which separate the fields of interfaces formatted by Prettier, ignoring trailing ones
+fn split(list: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    scan(list, |index, c, depth| {
+        if depth == 1 && matches!(c, ',' | ';') {
+            parts.push(&list[start..index]);
+            start = index + 1;
+        }
+        None::<()>
+    });
+    parts.push(&list[start..]);
+
+    parts.into_iter().map(str::trim).filter(|part| !part.is_empty()).collect()
+}
+
+fn split_once(member: &str, separator: char) -> Option<(&str, &str)> {
+    let index = scan(member, |index, c, depth| (depth == 1 && c == separator).then_some(index))?;
+
+    Some((&member[..index], &member[index + 1..]))
+}
+
+/// Calls `f` with each character outside of strings and its nesting depth, which starts at 1 and is 0 for a closing bracket that ends the scanned part.
+/// Stops at the first value returned by `f`.
+fn scan<T>(text: &str, mut f: impl FnMut(usize, char, usize) -> Option<T>) -> Option<T> {
+    let mut open = vec![];
+    let mut ended = false;
+    let mut quote = None;
+    let mut prev = ' ';
+    for (index, c) in text.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q && prev != '\\' => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '(' | '[' | '{') => open.push(c),
+            // Type arguments follow a name directly, unlike the comparison `a < b` in function bodies
+            (None, '<') if prev.is_alphanumeric() || prev == '_' => open.push(c),
+            // Arrows of function types, i.e. `(payload: T) => void`, and comparisons do not close a bracket
+            (None, '>') if prev == '=' || open.last() != Some(&'<') => {}
+            (None, ')' | ']' | '}' | '>') => ended = open.pop().is_none(),
+            _ => {}
+        }
+        let depth = if ended { 0 } else { open.len() + 1 };
+        ended = false;
+        if quote.is_none() {
+            if let Some(result) = f(index, c, depth) {
+                return Some(result);
+            }
+        }
+        prev = c;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERFACE: &str = "// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.\n\nexport interface User { name: string, tags: Record<string, Array<number>>, \"display-name\"?: string, }";
+    const FORMATTED_INTERFACE: &str = "// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.\n\nexport interface User {\n  name: string;\n  /** Tags of the user, keyed by name */\n  tags: Record<\n    string,\n    Array<number>\n  >;\n  \"display-name\"?: string;\n}\n";
+
+    const COMMAND: &str = "import { invoke } from \"@tauri-apps/api/tauri\"\n\nexport async function greet(user: User, formal?: boolean): Promise<string> { return invoke(\"greet\", { user, formal }) }";
+    const FORMATTED_COMMAND: &str = "import { invoke } from \"@tauri-apps/api/tauri\";\n\n/**\n * Greets the user\n */\nexport async function greet(\n  user: User,\n  formal?: boolean,\n): Promise<string> {\n  return invoke(\"greet\", { user, formal });\n}\n";
+
+    #[test]
+    fn fingerprint_reads_members() {
+        let members = fingerprint(&format!("{INTERFACE}\n{COMMAND}\nexport type Id = string;"));
+        let keys = members.keys().map(String::as_str).collect::<Vec<_>>();
+
+        assert_eq!(keys, ["Id", "User.display-name", "User.name", "User.tags", "greet()", "greet(formal)", "greet(user)"]);
+        assert_eq!(members["User.tags"].ty, "Record<string, Array<number>>");
+        assert!(members["User.display-name"].optional);
+        assert!(members["greet(user)"].is_param && !members["greet(user)"].optional);
+        assert_eq!(members["greet()"].ty, "Promise<string>");
+    }
+
+    #[test]
+    fn formatting_is_not_a_change() {
+        assert_eq!(breaking_changes(INTERFACE, FORMATTED_INTERFACE), Vec::<String>::new());
+        assert_eq!(breaking_changes(FORMATTED_INTERFACE, INTERFACE), Vec::<String>::new());
+        assert_eq!(breaking_changes(COMMAND, FORMATTED_COMMAND), Vec::<String>::new());
+        assert_eq!(breaking_changes("export type Dir = \"Up\" | \"Down\";", "export type Dir =\n  | \"Up\"\n  | \"Down\";\n"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn formatted_bindings_report_changes() {
+        let changed = FORMATTED_INTERFACE.replace("  name: string;\n", "").replace("Array<number>", "Array<string>").replace("\"display-name\"?", "\"display-name\"");
+
+        assert_eq!(breaking_changes(INTERFACE, &changed), [
+            "`User.display-name` is no longer optional",
+            "`User.name` was removed",
+            "`User.tags` changed from `Record<string, Array<number>>` to `Record< string, Array<string> >`",
+        ]);
+    }
+
+    #[test]
+    fn added_parameters_are_breaking_if_required() {
+        let optional = COMMAND.replace("formal?: boolean", "formal?: boolean, loud?: boolean");
+        let required = FORMATTED_COMMAND.replace("  formal?: boolean,\n", "  formal?: boolean,\n  loud: boolean,\n");
+
+        assert_eq!(breaking_changes(COMMAND, &optional), Vec::<String>::new());
+        assert_eq!(breaking_changes(COMMAND, &required), ["`greet(loud)` was added as required parameter"]);
+    }
+
+    #[test]
+    fn comments_and_strings_are_not_declarations() {
+        let content = "/*\nexport interface Old { a: string }\n*/\n// export type Gone = string\nexport const api = { greet: \"export type Fake = 1\" }\nexport type Id = string";
+
+        assert_eq!(fingerprint(content).keys().collect::<Vec<_>>(), ["Id"]);
+    }
+
+    #[test]
+    fn comparisons_in_bodies_do_not_nest() {
+        let content = "export function isSmall(value: number): boolean { return value < 10 }\nexport function isLarge(value: number): boolean {\n  return value > 10\n}";
+
+        assert_eq!(fingerprint(content).keys().collect::<Vec<_>>(), ["isLarge()", "isLarge(value)", "isSmall()", "isSmall(value)"]);
+    }
+}
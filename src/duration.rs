@@ -0,0 +1,34 @@
+//! Serializes `std::time::Duration` as number of milliseconds instead of `{ secs, nanos }`. Used by `#[entity(duration_as_ms)]`.
+
+/// Use with ```#[serde(with = "tauri_bindgen_ts::duration::millis")]```
+pub mod millis {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis().try_into().unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+
+    /// Same for `Option<Duration>`, use with ```#[serde(with = "tauri_bindgen_ts::duration::millis::option")]```
+    pub mod option {
+        use std::time::Duration;
+
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+            match duration {
+                Some(duration) => super::serialize(duration, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+            Option::<u64>::deserialize(deserializer).map(|millis| millis.map(Duration::from_millis))
+        }
+    }
+}
@@ -0,0 +1,1024 @@
+//! Helpers for writing generated bindings. These are called by the tests generated by `#[entity]` and `#[command]`.
+
+use std::{collections::BTreeMap, env, fs, io::{self, Read, Write}, marker::PhantomData, path::{Component, Path, PathBuf}, process::{Command, Stdio}, sync::{atomic::{AtomicBool, Ordering}, Mutex}, thread, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
+
+use ts_rs::{Dependency, TS};
+
+use crate::compat;
+
+/// Tests run in parallel, so updates of the index file and the overview of bindings need to be serialized
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+/// Whether bindings are formatted with Prettier, see [`use_prettier`]
+static PRETTIER: AtomicBool = AtomicBool::new(false);
+
+/// Set once Prettier failed, as each generated test enables it again
+static PRETTIER_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Time after which a Prettier process is killed and the binding is written without formatting
+const PRETTIER_TIMEOUT: Duration = Duration::from_secs(10);
+
+const INDEX_HEADER: &str = "// This file was generated by [tauri-bindgen-ts](https://github.com/antoniusnaumann/tauri-bindgen-ts). Do not edit this file manually.";
+
+/// Environment variable which forces writing bindings even if their content did not change, i.e. ```TAURI_BINDGEN_FORCE=1 cargo test```
+pub const FORCE_VAR: &str = "TAURI_BINDGEN_FORCE";
+
+/// Environment variable which turns breaking changes of existing bindings into errors, i.e. ```TAURI_BINDGEN_DENY_BREAKING=1 cargo test```
+pub const DENY_BREAKING_VAR: &str = "TAURI_BINDGEN_DENY_BREAKING";
+
+/// Environment variable which prints generated bindings to stdout instead of writing them, i.e. ```TAURI_BINDGEN_DRY_RUN=1 cargo test -- --nocapture```
+/// As nothing is written, each printed index file only contains the existing entries and the one being added.
+pub const DRY_RUN_VAR: &str = "TAURI_BINDGEN_DRY_RUN";
+
+/// Environment variable which compares generated bindings in the given directory to the existing files instead of writing them, set by [`crate::verify`]
+pub const VERIFY_VAR: &str = "TAURI_BINDGEN_VERIFY";
+
+/// Environment variable which omits the time of generation from bindings, so that generating them is reproducible, i.e. ```TAURI_BINDGEN_NO_TIMESTAMP=1 cargo test```
+pub const NO_TIMESTAMP_VAR: &str = "TAURI_BINDGEN_NO_TIMESTAMP";
+
+/// Start of the first line of bindings, both of those generated by this crate and by ts_rs
+const HEADER_PREFIX: &str = "// This file was generated by ";
+
+const SOURCE_PREFIX: &str = "// source: ";
+
+const TIMESTAMP_PREFIX: &str = "// generated at: ";
+
+/// Writes a generated binding to `file`, creating its parent directories if necessary.
+/// Files that already have the same content apart from the time of generation are not touched, so that frontend tooling does not pick up unchanged bindings.
+///
+/// Breaking changes to an existing binding, such as removed fields or changed parameter types, are reported as warning.
+/// Warnings are written to stderr directly, as the test harness hides all output of passing tests that is printed with ```eprintln!```.
+/// Set [`DENY_BREAKING_VAR`] to fail the test instead.
+/// With [`DRY_RUN_VAR`] set, the content is printed instead and the file system is not touched.
+pub fn write(file: impl AsRef<Path>, content: &str) {
+    write_reporting(file.as_ref(), content, |warning| {
+        let _ = writeln!(io::stderr(), "warning: {warning}");
+    });
+}
+
+/// Same as [`write`], but reports breaking changes with the given function
+pub(crate) fn write_reporting(file: &Path, content: &str, warn: impl Fn(&str)) {
+    let formatted = format(file, content, &warn);
+    let content = formatted.as_deref().unwrap_or(content);
+    if let Some(dir) = env::var_os(VERIFY_VAR) {
+        crate::verify::check(file, content, Path::new(&dir));
+        return;
+    }
+    let existing = fs::read_to_string(file).ok();
+    let dry_run = is_set(DRY_RUN_VAR);
+    // Bindings are not written again only because they were generated at a different time
+    if !is_set(FORCE_VAR) && !dry_run && existing.as_deref().map(without_timestamp) == Some(without_timestamp(content)) {
+        return;
+    }
+
+    let changes = existing.map(|existing| compat::breaking_changes(&existing, content)).unwrap_or_default();
+    if !changes.is_empty() {
+        let message = format!("Breaking changes in {}: {}", file.display(), changes.join(", "));
+        if is_set(DENY_BREAKING_VAR) {
+            panic!("{message}. Unset {DENY_BREAKING_VAR} to accept these changes");
+        }
+        warn(&message);
+    }
+
+    if dry_run {
+        println!("--- {}\n{content}", file.display());
+        return;
+    }
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent).expect("Could not create directory");
+    }
+    fs::write(file, content).expect("Could not write generated binding to file");
+}
+
+/// Adds the location of the annotated item, i.e. "src/lib.rs:12", and the time of generation below the header of a binding.
+/// See [`NO_TIMESTAMP_VAR`] to omit the time.
+pub fn add_metadata(content: &str, source: &str) -> String {
+    let mut metadata = format!("{SOURCE_PREFIX}{source}");
+    if !is_set(NO_TIMESTAMP_VAR) {
+        metadata.push_str(&format!("\n{TIMESTAMP_PREFIX}{}", timestamp(SystemTime::now())));
+    }
+
+    let mut lines = content.split('\n').collect::<Vec<_>>();
+    let position = lines.iter().position(|line| line.starts_with(HEADER_PREFIX)).map_or(0, |index| index + 1);
+    lines.insert(position, &metadata);
+    lines.join("\n")
+}
+
+/// Content of a binding without the time at which it was generated, which does not change the binding itself
+pub(crate) fn without_timestamp(content: &str) -> String {
+    content.split('\n').filter(|line| !line.starts_with(TIMESTAMP_PREFIX)).collect::<Vec<_>>().join("\n")
+}
+
+/// Time in UTC as in ISO 8601, i.e. "2024-05-01T12:30:00Z"
+fn timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+    let (days, secs) = (secs / 86_400, secs % 86_400);
+    // Converts days since 1970-01-01 to a date of the proleptic Gregorian calendar, with years starting in March
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z", secs / 3_600, secs / 60 % 60, secs % 60)
+}
+
+/// Formats all TypeScript bindings that are written afterwards with Prettier, which is called by the generated tests if ```format = "prettier"``` is configured.
+/// Prettier needs to be installed in the frontend project, otherwise `npx` is used. If it fails or does not finish in time, formatting is skipped with a warning.
+pub fn use_prettier() {
+    PRETTIER.store(true, Ordering::Relaxed);
+}
+
+/// Content formatted with Prettier, which is done before comparing it to the existing file so that unchanged bindings are not written again.
+/// Index files are left as is, as these are updated line by line.
+fn format(file: &Path, content: &str, warn: impl Fn(&str)) -> Option<String> {
+    let is_binding = file.extension().is_some_and(|ext| ext == "ts") && file.file_name().is_some_and(|name| name != "index.ts");
+    if !is_binding || !PRETTIER.load(Ordering::Relaxed) || PRETTIER_FAILED.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    match prettier(file, content) {
+        Ok(formatted) => Some(formatted),
+        Err(error) => {
+            // Only warn once instead of for every binding
+            if !PRETTIER_FAILED.swap(true, Ordering::Relaxed) {
+                warn(&format!("{error}, bindings are written without formatting. Install Prettier in the frontend project with `npm install --save-dev prettier`"));
+            }
+            None
+        }
+    }
+}
+
+/// Runs the Prettier of the closest `node_modules` or falls back to `npx`, which tries to download it and hangs without network access.
+/// Prettier is killed after [`PRETTIER_TIMEOUT`], so that generating the bindings never blocks.
+fn prettier(file: &Path, content: &str) -> Result<String, String> {
+    let (program, args) = match installed_prettier(file) {
+        Some(prettier) => (prettier.into_os_string(), vec![]),
+        None => ((if cfg!(windows) { "npx.cmd" } else { "npx" }).into(), vec!["--no-install", "prettier"]),
+    };
+    // Prettier picks the parser and resolves its config using the path of the file, which does not need to exist
+    let mut child = Command::new(&program)
+        .args(args)
+        .arg("--stdin-filepath")
+        .arg(file)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("Could not run {}: {err}", program.to_string_lossy()))?;
+
+    // Input and output are passed on separate threads, as the child would block on a full pipe otherwise
+    let (mut stdin, mut stdout) = (child.stdin.take().expect("stdin is piped"), child.stdout.take().expect("stdout is piped"));
+    let content = content.to_owned();
+    thread::spawn(move || stdin.write_all(content.as_bytes()));
+    let output = thread::spawn(move || {
+        let mut output = String::new();
+        stdout.read_to_string(&mut output).map(|_| output)
+    });
+
+    let deadline = Instant::now() + PRETTIER_TIMEOUT;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("Prettier did not finish within {} seconds", PRETTIER_TIMEOUT.as_secs()));
+            }
+        }
+    };
+    if !status.success() {
+        return Err(format!("Prettier failed with {status}"));
+    }
+
+    output.join().ok().and_then(Result::ok).ok_or_else(|| "Could not read the output of Prettier".to_owned())
+}
+
+/// Executable of Prettier in the `node_modules` of the export directory or one of its parents, i.e. the root of the Tauri app
+fn installed_prettier(file: &Path) -> Option<PathBuf> {
+    let name = if cfg!(windows) { "prettier.cmd" } else { "prettier" };
+    let file = env::current_dir().ok()?.join(file);
+
+    file.ancestors().skip(1).map(|dir| dir.join("node_modules").join(".bin").join(name)).find(|path| path.is_file())
+}
+
+fn is_set(var: &str) -> bool {
+    env::var(var).is_ok_and(|value| !matches!(value.as_str(), "" | "0" | "false"))
+}
+
+const DURATION_HELPERS: &str = r#"
+/**
+ * Serialized form of `std::time::Duration`
+ */
+export interface Duration { secs: number, nanos: number }
+
+/**
+ * Converts a duration to milliseconds
+ */
+export function durationToMillis(duration: Duration): number { return duration.secs * 1000 + duration.nanos / 1_000_000 }
+
+/**
+ * Converts milliseconds to a duration
+ */
+export function durationFromMillis(millis: number): Duration { const secs = Math.floor(millis / 1000); return { secs, nanos: Math.round((millis - secs * 1000) * 1_000_000) } }
+"#;
+
+/// Writes helpers for converting the serialized form of `std::time::Duration` to the file "duration.ts" in `dir` and re-exports them from its index file
+pub fn add_duration_helpers(dir: impl AsRef<Path>) {
+    let dir = dir.as_ref();
+    write(dir.join("duration.ts"), &format!("{INDEX_HEADER}\n{DURATION_HELPERS}"));
+    add_to_index(dir, "export * from \"./duration\"".to_owned());
+}
+
+/// Adds a re-export of the command binding `name` to the index file in `dir`
+pub fn add_command_to_index(dir: impl AsRef<Path>, name: &str) {
+    add_to_index(dir.as_ref(), format!("export * from \"./{name}\""));
+}
+
+/// Adds a re-export of the entity type `name` to the index file in `dir`
+pub fn add_entity_to_index(dir: impl AsRef<Path>, name: &str) {
+    add_to_index(dir.as_ref(), format!("export type * from \"./{name}\""));
+}
+
+/// Adds a re-export of the entity type `name` and its values, such as its type guard, to the index file in `dir`
+pub fn add_guarded_entity_to_index(dir: impl AsRef<Path>, name: &str) {
+    add_to_index(dir.as_ref(), format!("export * from \"./{name}\""));
+}
+
+/// Adds a re-export of the Zod schema of the entity type `name` to the index file in `dir`
+pub fn add_schema_to_index(dir: impl AsRef<Path>, name: &str) {
+    add_to_index(dir.as_ref(), format!("export * from \"./{name}.zod\""));
+}
+
+/// Dialect of generated JSON Schemas, which supports ```prefixItems``` for tuples
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Writes the JSON Schema of the entity type `name` to the file "{name}.schema.json" in `dir`, declaring its dialect and title
+pub fn write_json_schema(dir: impl AsRef<Path>, name: &str, schema: &str) {
+    let mut schema = parse_json_schema(schema);
+    if let Some(object) = schema.as_object_mut() {
+        object.insert("$schema".to_owned(), JSON_SCHEMA_DIALECT.into());
+        object.insert("title".to_owned(), name.into());
+    }
+    let content = serde_json::to_string_pretty(&schema).expect("JSON Schema should be serializable");
+    write(dir.as_ref().join(format!("{name}.schema.json")), &format!("{content}\n"));
+}
+
+/// Adds a description to a JSON Schema, i.e. the doc comment of an entity or field
+pub fn describe_json_schema(schema: &str, description: &str) -> String {
+    let mut schema = parse_json_schema(schema);
+    if let Some(object) = schema.as_object_mut() {
+        object.insert("description".to_owned(), description.into());
+    }
+    schema.to_string()
+}
+
+fn parse_json_schema(schema: &str) -> serde_json::Value {
+    serde_json::from_str(schema).expect("Generated JSON Schema should be valid JSON")
+}
+
+/// Adds a re-export of the index file of the subdirectory `module` to the index file in `dir`
+pub fn add_module_to_index(dir: impl AsRef<Path>, module: &str) {
+    add_to_index(dir.as_ref(), format!("export * from \"./{module}\""));
+}
+
+/// Declaration of the commands in the index file, i.e. ```export const commandList = ["greet"] as const```
+const COMMAND_LIST: (&str, &str) = ("export const commandList = ", " as const");
+
+/// Declared below the command list, so that frontend code can narrow strings to the name of a command
+const COMMAND_NAME: &str = "export type CommandName = typeof commandList[number]";
+
+/// Adds the command that is invoked with `name` to ```commandList``` in the index file in `dir`, which lists all commands of the export directory including its modules
+pub fn add_command_to_list(dir: impl AsRef<Path>, name: &str) {
+    update_index(dir.as_ref(), None, Some(name));
+}
+
+/// Merges `line` into the existing index file. Lines are sorted, so the file content does not depend on the order in which bindings are generated.
+fn add_to_index(dir: &Path, line: String) {
+    update_index(dir, Some(line), None);
+}
+
+/// Re-exports are sorted and followed by the command list, whose names are sorted as well
+fn update_index(dir: &Path, line: Option<String>, command: Option<&str>) {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let file = dir.join("index.ts");
+
+    let existing = fs::read_to_string(&file).unwrap_or_default();
+    let mut commands = existing.lines()
+        .find_map(|line| line.strip_prefix(COMMAND_LIST.0)?.strip_suffix(COMMAND_LIST.1))
+        .and_then(|list| serde_json::from_str::<Vec<String>>(list).ok())
+        .unwrap_or_default();
+    commands.extend(command.map(str::to_owned));
+    commands.sort();
+    commands.dedup();
+
+    let mut lines = existing.lines()
+        .filter(|line| line.starts_with("export ") && !line.starts_with(COMMAND_LIST.0) && *line != COMMAND_NAME)
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    lines.extend(line);
+    lines.sort();
+    lines.dedup();
+    if !commands.is_empty() {
+        let list = serde_json::to_string(&commands).expect("Command names should be serializable").replace("\",\"", "\", \"");
+        lines.push(format!("{}{list}{}", COMMAND_LIST.0, COMMAND_LIST.1));
+        lines.push(COMMAND_NAME.to_owned());
+    }
+
+    write(&file, &format!("{INDEX_HEADER}\n{}\n", lines.join("\n")));
+}
+
+/// Overview of all bindings in the export directory, which is written if ```generate_readme = true``` is configured
+const README_FILE: &str = "BINDINGS.md";
+
+/// Sections of the overview in the order in which they are listed
+const README_SECTIONS: [&str; 2] = ["Commands", "Entities"];
+
+/// Adds the command `name` with its signature such as ```greet(name: string): Promise<string>``` and its documentation to the overview in `dir`
+pub fn add_command_to_readme(dir: impl AsRef<Path>, name: &str, signature: &str, doc: &str) {
+    add_to_readme(dir.as_ref(), 0, name, signature, doc);
+}
+
+/// Adds the entity `name` with its declaration such as ```interface User { name: string, }``` and its documentation to the overview in `dir`
+pub fn add_entity_to_readme(dir: impl AsRef<Path>, name: &str, declaration: &str, doc: &str) {
+    add_to_readme(dir.as_ref(), 1, name, declaration, doc);
+}
+
+/// Merges the entry into the existing overview. Same as for the index file, entries are sorted by name within their section.
+fn add_to_readme(dir: &Path, section: usize, name: &str, declaration: &str, doc: &str) {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let file = dir.join(README_FILE);
+
+    let mut entries = readme_entries(&fs::read_to_string(&file).unwrap_or_default());
+    let doc = match doc.trim() {
+        "" => String::new(),
+        doc => format!("\n\n{doc}"),
+    };
+    entries.insert((section, name.to_owned()), format!("### `{name}`{doc}\n\n```ts\n{declaration}\n```"));
+
+    let mut content = format!("<!-- {} -->\n\n# Bindings\n", INDEX_HEADER.trim_start_matches("// "));
+    for (index, title) in README_SECTIONS.iter().enumerate() {
+        let section = entries.iter().filter(|((section, _), _)| *section == index).map(|(_, entry)| entry.as_str()).collect::<Vec<_>>();
+        if !section.is_empty() {
+            content.push_str(&format!("\n## {title}\n\n{}\n", section.join("\n\n")));
+        }
+    }
+    write(&file, &content);
+}
+
+/// Entries of an existing overview keyed by their section and name, each starting with its heading
+fn readme_entries(content: &str) -> BTreeMap<(usize, String), String> {
+    let mut entries = BTreeMap::new();
+    let mut section = None;
+    let mut entry: Option<((usize, String), Vec<&str>)> = None;
+    for line in content.lines() {
+        let title = line.strip_prefix("## ");
+        let name = line.strip_prefix("### ").map(|name| name.trim_matches('`').to_owned());
+        if title.is_some() || name.is_some() {
+            entries.extend(entry.take().map(|(key, lines)| (key, lines.join("\n").trim().to_owned())));
+        }
+        if let Some(title) = title {
+            section = README_SECTIONS.iter().position(|section| *section == title);
+        }
+        match (section, name, &mut entry) {
+            (Some(section), Some(name), _) => entry = Some(((section, name), vec![line])),
+            (_, None, Some((_, lines))) => lines.push(line),
+            _ => {}
+        }
+    }
+    entries.extend(entry.map(|(key, lines)| (key, lines.join("\n").trim().to_owned())));
+
+    entries
+}
+
+/// Permissions required by the commands in the export directory, which can be merged into a capability of the Tauri v2 ACL
+const PERMISSIONS_FILE: &str = "permissions.json";
+
+/// Adds the permissions required by a command to "permissions.json" in `dir`, i.e. ```{ "permissions": ["fs:allow-read"] }```
+pub fn add_permissions(dir: impl AsRef<Path>, permissions: &[&str]) {
+    let _guard = INDEX_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let file = dir.as_ref().join(PERMISSIONS_FILE);
+
+    let existing = fs::read_to_string(&file).ok().and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok());
+    let mut all = existing.as_ref()
+        .and_then(|existing| existing["permissions"].as_array())
+        .map(|permissions| permissions.iter().filter_map(|permission| permission.as_str().map(str::to_owned)).collect::<Vec<_>>())
+        .unwrap_or_default();
+    all.extend(permissions.iter().map(|permission| permission.to_string()));
+    all.sort();
+    all.dedup();
+
+    let content = serde_json::to_string_pretty(&serde_json::json!({ "permissions": all })).expect("Permissions should be serializable");
+    write(&file, &format!("{content}\n"));
+}
+
+/// Looks up the TypeScript name of `T` if it is an exported entity, without requiring `T` to implement `TS`.
+/// Bring both [`ExportedName`] and [`NotExported`] into scope and call ```(&Probe::<T>::default()).exported_name()```.
+pub struct Probe<T>(PhantomData<T>);
+
+impl<T> Default for Probe<T> {
+    fn default() -> Self {
+        Probe(PhantomData)
+    }
+}
+
+/// Method resolution prefers this implementation if `T` implements `TS`
+pub trait ExportedName {
+    fn exported_name(&self) -> Option<String>;
+}
+
+impl<T: TS> ExportedName for Probe<T> {
+    fn exported_name(&self) -> Option<String> {
+        Dependency::from_ty::<T>().map(|dep| dep.ts_name)
+    }
+}
+
+/// Fallback for types that don't implement `TS`, which requires an additional auto-ref during method resolution
+pub trait NotExported {
+    fn exported_name(&self) -> Option<String>;
+}
+
+impl<T> NotExported for &Probe<T> {
+    fn exported_name(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Implemented by entities with ```#[entity(guard)]```, so that other bindings can call their type guard
+#[diagnostic::on_unimplemented(
+    message = "the type guard of `{Self}` is called by another guard, but is not generated",
+    label = "`{Self}` has no type guard",
+    note = "annotate `{Self}` with `#[entity(guard)]` as well"
+)]
+pub trait Guarded {}
+
+/// Fails to compile if `T` has no type guard, see [`Guarded`]
+pub const fn assert_guarded<T: Guarded + ?Sized>() {}
+
+/// Implemented by entities with ```#[entity(zod)]```, whose schema other schemas import
+#[diagnostic::on_unimplemented(
+    message = "the Zod schema of `{Self}` is imported by another schema, but is not generated",
+    label = "`{Self}` has no Zod schema",
+    note = "annotate `{Self}` with `#[entity(zod)]` as well"
+)]
+pub trait HasZodSchema {}
+
+/// Fails to compile if `T` has no Zod schema, see [`HasZodSchema`]
+pub const fn assert_zod_schema<T: HasZodSchema + ?Sized>() {}
+
+/// Implemented by entities with ```#[entity(json_schema)]```, whose schema other schemas reference
+#[diagnostic::on_unimplemented(
+    message = "the JSON Schema of `{Self}` is referenced by another schema, but is not generated",
+    label = "`{Self}` has no JSON Schema",
+    note = "annotate `{Self}` with `#[entity(json_schema)]` as well"
+)]
+pub trait HasJsonSchema {}
+
+/// Fails to compile if `T` has no JSON Schema, see [`HasJsonSchema`]
+pub const fn assert_json_schema<T: HasJsonSchema + ?Sized>() {}
+
+/// Method resolution prefers this implementation if `T` has a type guard. Call ```(&Probe::<T>::default()).has_guard()```, same as for [`ExportedName`].
+pub trait HasGuard {
+    fn has_guard(&self) -> bool;
+}
+
+impl<T: Guarded> HasGuard for Probe<T> {
+    fn has_guard(&self) -> bool {
+        true
+    }
+}
+
+/// Fallback for types without a type guard
+pub trait NoGuard {
+    fn has_guard(&self) -> bool;
+}
+
+impl<T> NoGuard for &Probe<T> {
+    fn has_guard(&self) -> bool {
+        false
+    }
+}
+
+/// Implemented by entities with ```#[entity(factory)]```, whose factory is called by the factories of other entities
+pub trait WithFactory {}
+
+/// Method resolution prefers this implementation if `T` has a factory. Call ```(&Probe::<T>::default()).has_factory()```, same as for [`ExportedName`].
+pub trait HasFactory {
+    fn has_factory(&self) -> bool;
+}
+
+impl<T: WithFactory> HasFactory for Probe<T> {
+    fn has_factory(&self) -> bool {
+        true
+    }
+}
+
+/// Fallback for types without a factory
+pub trait NoFactory {
+    fn has_factory(&self) -> bool;
+}
+
+impl<T> NoFactory for &Probe<T> {
+    fn has_factory(&self) -> bool {
+        false
+    }
+}
+
+/// Method resolution prefers this implementation if `T` implements `Default`. Call ```(&Probe::<T>::default()).default_value()```, same as for [`ExportedName`].
+pub trait DefaultValue {
+    /// The serialized default of `T`, which is a valid TypeScript value as well
+    fn default_value(&self) -> Option<String>;
+}
+
+impl<T: Default + serde::Serialize> DefaultValue for Probe<T> {
+    fn default_value(&self) -> Option<String> {
+        serde_json::to_string(&T::default()).ok()
+    }
+}
+
+/// Fallback for types without a default
+pub trait NoDefaultValue {
+    fn default_value(&self) -> Option<String>;
+}
+
+impl<T> NoDefaultValue for &Probe<T> {
+    fn default_value(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Implemented by entities with ```#[entity(import_from = "...")]```, which other bindings import from the given path instead of the export directory
+pub trait ImportFrom {
+    const IMPORT_FROM: &'static str;
+}
+
+/// Method resolution prefers this implementation if `T` specifies the path it is imported from. Call ```(&Probe::<T>::default()).import_from()```, same as for [`ExportedName`].
+pub trait CustomImport {
+    fn import_from(&self) -> Option<&'static str>;
+}
+
+impl<T: ImportFrom> CustomImport for Probe<T> {
+    fn import_from(&self) -> Option<&'static str> {
+        Some(T::IMPORT_FROM)
+    }
+}
+
+/// Fallback for types that are imported from the export directory
+pub trait DefaultImport {
+    fn import_from(&self) -> Option<&'static str>;
+}
+
+impl<T> DefaultImport for &Probe<T> {
+    fn import_from(&self) -> Option<&'static str> {
+        None
+    }
+}
+
+/// Implemented for all types with a TypeScript binding. Commands require it for the types they use, so that a missing ```#[entity]``` fails to compile instead of referencing an undeclared type.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is used by a command, but has no TypeScript binding",
+    label = "no TypeScript binding for `{Self}`",
+    note = "annotate `{Self}` with `#[entity]`, so that its TypeScript type is generated as well"
+)]
+pub trait HasBinding {}
+
+impl<T: TS + ?Sized> HasBinding for T {}
+
+/// Fails to compile if `T` has no TypeScript binding, see [`HasBinding`]
+pub const fn assert_binding<T: HasBinding + ?Sized>() {}
+
+/// Replaces the path from which the type `name` is imported in the content of a generated file
+pub fn redirect_import(content: &str, name: &str, path: &str) -> String {
+    let import = format!("import type {{ {name} }} from ");
+    content.split('\n')
+        .map(|line| match line.starts_with(&import) {
+            true => format!("{import}\"{path}\";"),
+            false => line.to_owned(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Path of an import from a file in a subdirectory such as "\_\_mocks\_\_", given the path from the export directory. Paths that are not relative, i.e. aliases such as "@common/User", are kept.
+pub fn import_from_subdirectory(path: &str) -> String {
+    match path.strip_prefix("./") {
+        Some(path) => format!("../{path}"),
+        None if path.starts_with("../") => format!("../{path}"),
+        None => path.to_owned(),
+    }
+}
+
+/// Writes the type guard ```catchMyError(e: unknown): e is MyError``` for errors with which commands reject to its own file in `dir`, as it is shared by all commands with the same error type.
+/// The error is narrowed to `ty`. If it is an entity, it is imported from `import` and its type guard is called if it has one. Otherwise, `check` is used to narrow the error.
+pub fn add_error_guard(dir: impl AsRef<Path>, name: &str, ty: &str, import: Option<&str>, guarded: bool, check: &str) {
+    let (imports, check) = match (import, guarded) {
+        (Some(import), true) => (format!("\nimport type {{ {ty} }} from \"{import}\"\nimport {{ is{ty} }} from \"{import}\""), format!("is{ty}(e)")),
+        (Some(import), false) => (format!("\nimport type {{ {ty} }} from \"{import}\""), check.to_owned()),
+        (None, _) => (String::new(), check.to_owned()),
+    };
+    let doc = format!("/**\n * Whether the error with which a command rejected is of type `{name}`\n */\n");
+    let content = format!("{INDEX_HEADER}{imports}\n\n{doc}export function catch{name}(e: unknown): e is {ty} {{ return {check} }}");
+
+    let dir = dir.as_ref();
+    write(dir.join(format!("catch{name}.ts")), &content);
+    add_to_index(dir, format!("export * from \"./catch{name}\""));
+}
+
+/// Adds the `readonly` modifier to the given top-level fields of an interface declaration such as ```interface User { name: string, }```
+pub fn readonly(decl: &str, fields: &[&str]) -> String {
+    edit_fields(decl, fields, |name| format!("readonly {name}"))
+}
+
+/// Marks the given top-level fields of an interface declaration as optional, i.e. ```interface User { name?: string, }```
+pub fn optional(decl: &str, fields: &[&str]) -> String {
+    edit_fields(decl, fields, |name| match name.ends_with('?') {
+        true => name.to_owned(),
+        false => format!("{name}?"),
+    })
+}
+
+/// Replaces the names of the given top-level fields, including quotes and the optional marker, with the result of `edit`
+fn edit_fields(decl: &str, fields: &[&str], edit: impl Fn(&str) -> String) -> String {
+    let Some(start) = decl.find('{') else { return decl.to_owned() };
+    let (head, body) = decl.split_at(start + 1);
+
+    let mut result = head.to_owned();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut expects_field = true;
+    let mut skip_to = 0;
+    let mut prev = ' ';
+    for (index, c) in body.char_indices() {
+        if index < skip_to {
+            continue;
+        }
+        if let Some(len) = comment_len(&body[index..]).filter(|_| quote.is_none()) {
+            result.push_str(&body[index..index + len]);
+            skip_to = index + len;
+            continue;
+        }
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (None, c) if expects_field && depth == 0 && !c.is_whitespace() => {
+                expects_field = false;
+                let declared = body[index..].split(':').next().unwrap_or_default().trim_end();
+                let name = declared.trim_end_matches('?').trim_matches(|c| c == '"' || c == '\'');
+                if fields.contains(&name) {
+                    result.push_str(&edit(declared));
+                    skip_to = index + declared.len();
+                    continue;
+                }
+                if c == '"' || c == '\'' {
+                    quote = Some(c);
+                }
+            }
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{' | '[' | '(' | '<') => depth += 1,
+            // Arrows of function types do not close a bracket
+            (None, '>') if prev == '=' => {}
+            (None, '}' | ']' | ')' | '>') => depth -= 1,
+            (None, ',') if depth == 0 => expects_field = true,
+            _ => {}
+        }
+        prev = c;
+        result.push(c);
+    }
+
+    result
+}
+
+/// Length of the comment at the start of `rest`, if it starts with one, i.e. ```/* a, b */``` or a line comment up to the end of the line
+fn comment_len(rest: &str) -> Option<usize> {
+    if rest.starts_with("/*") {
+        return Some(rest.find("*/").map_or(rest.len(), |end| end + 2));
+    }
+    rest.starts_with("//").then(|| rest.find('\n').unwrap_or(rest.len()))
+}
+
+/// Adds ```| null``` to the types of the given top-level fields of an interface declaration, i.e. ```interface User { name?: string | null, }```
+pub fn nullable(decl: &str, fields: &[&str]) -> String {
+    let (Some(start), Some(end)) = (decl.find('{'), decl.rfind('}')) else { return decl.to_owned() };
+    if !decl.starts_with("interface ") || end < start {
+        return decl.to_owned();
+    }
+
+    let body = split_fields(&decl[start + 1..end]).into_iter()
+        .map(|field| match fields.contains(&field_name(field)) && !field.ends_with("| null") {
+            true => format!("{field} | null"),
+            false => field.to_owned(),
+        })
+        .collect::<Vec<_>>();
+
+    format!("{} {}, {}", &decl[..=start], body.join(", "), &decl[end..])
+}
+
+/// Sorts the top-level fields of an interface declaration by name, ignoring case, so that reordering fields in Rust does not change the binding, i.e. ```interface User { age: number, name: string, }```
+pub fn sort_fields(decl: &str) -> String {
+    let (Some(start), Some(end)) = (decl.find('{'), decl.rfind('}')) else { return decl.to_owned() };
+    if !decl.starts_with("interface ") || end < start {
+        return decl.to_owned();
+    }
+
+    let mut fields = split_fields(&decl[start + 1..end]);
+    if fields.is_empty() {
+        return decl.to_owned();
+    }
+    fields.sort_by_cached_key(|field| (field_name(field).to_lowercase(), field_name(field).to_owned()));
+
+    format!("{} {}, {}", &decl[..=start], fields.join(", "), &decl[end..])
+}
+
+/// Splits the body of an interface at top-level commas, ignoring the trailing one
+fn split_fields(body: &str) -> Vec<&str> {
+    let mut fields = vec![];
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    let mut prev = ' ';
+    let mut skip_to = 0;
+    for (index, c) in body.char_indices() {
+        if index < skip_to {
+            continue;
+        }
+        if let Some(len) = comment_len(&body[index..]).filter(|_| quote.is_none()) {
+            skip_to = index + len;
+            continue;
+        }
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '{' | '[' | '(' | '<') => depth += 1,
+            // Arrows of function types do not close a bracket
+            (None, '>') if prev == '=' => {}
+            (None, '}' | ']' | ')' | '>') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                fields.push(body[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+    fields.push(body[start..].trim());
+
+    fields.into_iter().filter(|field| !field.is_empty()).collect()
+}
+
+/// Name of a field such as ```readonly "name"?: string```, without leading comments, modifiers and quotes
+fn field_name(mut field: &str) -> &str {
+    while let Some(len) = comment_len(field) {
+        field = field[len..].trim_start();
+    }
+    let field = field.strip_prefix("readonly ").unwrap_or(field);
+    let name = match field.strip_prefix(['"', '\'']) {
+        Some(quoted) => quoted.split(['"', '\'']).next().unwrap_or_default(),
+        None => field.split(':').next().unwrap_or_default(),
+    };
+
+    name.trim().trim_end_matches('?')
+}
+
+/// Sorts the imports following the header of a generated file, so that their order does not depend on the order in which they were added
+pub fn sort_imports(content: &str) -> String {
+    let (head, rest) = content.split_at(content.find("\n\n").unwrap_or(content.len()));
+    let (mut imports, other): (Vec<&str>, Vec<&str>) = head.lines().partition(|line| line.starts_with("import "));
+    imports.sort();
+
+    format!("{}{rest}", other.into_iter().chain(imports).collect::<Vec<_>>().join("\n"))
+}
+
+/// Replaces the header generated by ts_rs with the one of this crate if `content` differs from what ts_rs `generated`, as the file is not generated by ts_rs alone then
+pub fn replace_header(content: &str, generated: &str) -> String {
+    match content == generated {
+        true => content.to_owned(),
+        false => format!("{INDEX_HEADER}{}", &content[content.find('\n').unwrap_or(content.len())..]),
+    }
+}
+
+/// Turns an interface declaration into the intersection of the given types with its fields, i.e. ```type Response = Meta & { data: string, };```
+pub fn intersect(decl: &str, types: &[String]) -> String {
+    let Some((name, body)) = decl.strip_prefix("interface ").and_then(|decl| decl.split_once(" {")) else { return decl.to_owned() };
+    let mut types = types.to_vec();
+    // Structs that only consist of flattened fields do not need an empty object
+    if !body.trim().trim_end_matches('}').trim().is_empty() {
+        types.push(format!("{{{body}"));
+    }
+
+    format!("type {name} = {};", types.join(" & "))
+}
+
+/// Lets an interface declaration extend the given types instead of intersecting them, i.e. ```interface Response extends Meta { data: string, }```
+pub fn extend(decl: &str, types: &[String]) -> String {
+    let Some((name, body)) = decl.strip_prefix("interface ").and_then(|decl| decl.split_once(" {")) else { return decl.to_owned() };
+
+    format!("interface {name} extends {} {{{body}", types.join(", "))
+}
+
+/// Turns an interface declaration into a type alias of an object type, i.e. ```type User = { name: string, };```
+pub fn type_alias(decl: &str) -> String {
+    let Some((name, body)) = decl.strip_prefix("interface ").and_then(|decl| decl.split_once(" {")) else { return decl.to_owned() };
+
+    format!("type {name} = {{{body};")
+}
+
+/// Intersects a type alias with a brand, i.e. ```type UserId = number & { readonly __brand: "UserId" };```
+pub fn brand(decl: &str, name: &str) -> String {
+    let Some((head, ty)) = decl.split_once(" = ") else { return decl.to_owned() };
+    let ty = ty.trim_end_matches(';');
+    // Intersecting null with an object would be never, so only the value is branded
+    let (ty, null) = match ty.strip_suffix(" | null") {
+        Some(ty) => (ty, " | null"),
+        None => (ty, ""),
+    };
+    let ty = if ty.contains(" | ") { format!("({ty})") } else { ty.to_owned() };
+
+    format!("{head} = {ty} & {{ readonly __brand: \"{name}\" }}{null};")
+}
+
+/// Adds imports of the given types to the content of the entity file `file`, unless these are already imported
+pub fn add_imports(content: &str, file: &str, dependencies: &[Dependency]) -> String {
+    let imports = dependencies.iter()
+        .filter(|dependency| !content.contains(&format!("import type {{ {} }} from ", dependency.ts_name)))
+        .map(|dependency| format!("\nimport type {{ {} }} from \"{}\";", dependency.ts_name, import_path(file, dependency.exported_to)))
+        .collect::<String>();
+
+    // Imports generated by ts_rs follow the header and are separated from the declaration by an empty line
+    match content.find("\n\n") {
+        Some(index) => format!("{}{imports}{}", &content[..index], &content[index..]),
+        None => content.to_owned(),
+    }
+}
+
+/// Path of the file `to` relative to the file `from` without extension, i.e. `../Meta` for `models/Book.ts` and `Meta.ts`
+fn import_path(from: &str, to: &str) -> String {
+    let from = components(Path::new(from).parent().unwrap_or(Path::new("")));
+    let to_path = Path::new(to).with_extension("");
+    let to = components(&to_path);
+
+    let common = from.iter().zip(&to).take_while(|(from, to)| from == to).count();
+    let parents = vec![".."; from.len() - common];
+    let rest = to[common..].iter().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>();
+
+    match parents.is_empty() {
+        true => format!("./{}", rest.join("/")),
+        false => format!("{}/{}", parents.join("/"), rest.join("/")),
+    }
+}
+
+fn components(path: &Path) -> Vec<Component<'_>> {
+    path.components().filter(|component| !matches!(component, Component::CurDir)).collect()
+}
+
+/// Appends a type guard to the content of an entity file. Guards of the `dependencies` are imported from the same file as their type.
+pub fn add_guard(content: &str, guard: &str, dependencies: &[String]) -> String {
+    add_value(content, guard, dependencies, "is")
+}
+
+/// Appends a factory to the content of an entity file. Factories of the `dependencies` are imported from the same file as their type.
+pub fn add_factory(content: &str, factory: &str, dependencies: &[String]) -> String {
+    add_value(content, factory, dependencies, "create")
+}
+
+/// Appends a function to the content of an entity file, importing the functions with the given prefix of the `dependencies`
+fn add_value(content: &str, value: &str, dependencies: &[String], prefix: &str) -> String {
+    let mut lines = vec![];
+    for line in content.lines() {
+        lines.push(line.to_owned());
+        let dependency = dependencies.iter().find(|name| line.starts_with(&format!("import type {{ {name} }} from ")));
+        if let (Some(name), Some((_, path))) = (dependency, line.split_once(" from ")) {
+            lines.push(format!("import {{ {prefix}{name} }} from {path}"));
+        }
+    }
+
+    format!("{}{value}", lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECL: &str = "interface User { name: string, tags: Record<string, Array<number>>, \"display-name\"?: string, pair: [string, number], callback: (a: number, b: string) => void, }";
+
+    #[test]
+    fn metadata_follows_the_header() {
+        let content = add_metadata("// @ts-nocheck\n// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.\n\nexport type Score = number;", "src/lib.rs:12");
+        let lines = content.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[2], "// source: src/lib.rs:12");
+        assert!(lines[3].starts_with("// generated at: "));
+        assert_eq!(without_timestamp(&content), "// @ts-nocheck\n// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.\n// source: src/lib.rs:12\n\nexport type Score = number;");
+    }
+
+    #[test]
+    fn timestamps_are_utc_dates_and_times() {
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(timestamp(UNIX_EPOCH + Duration::from_secs(951_827_696)), "2000-02-29T12:34:56Z");
+        assert_eq!(timestamp(UNIX_EPOCH + Duration::from_secs(1_735_689_599)), "2024-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn readonly_edits_only_the_given_top_level_fields() {
+        assert_eq!(
+            readonly(DECL, &["tags", "display-name", "a", "b"]),
+            "interface User { name: string, readonly tags: Record<string, Array<number>>, readonly \"display-name\"?: string, pair: [string, number], callback: (a: number, b: string) => void, }",
+        );
+    }
+
+    #[test]
+    fn optional_keeps_fields_that_are_already_optional() {
+        assert_eq!(
+            optional(DECL, &["name", "display-name", "callback"]),
+            "interface User { name?: string, tags: Record<string, Array<number>>, \"display-name\"?: string, pair: [string, number], callback?: (a: number, b: string) => void, }",
+        );
+    }
+
+    #[test]
+    fn edited_fields_may_follow_arrow_functions_and_comments() {
+        let decl = "interface Handler { run: () => Promise<void>, /* a, b */ after: Map<string, Set<number>>, 'quoted': string, }";
+
+        assert_eq!(
+            optional(decl, &["after", "quoted"]),
+            "interface Handler { run: () => Promise<void>, /* a, b */ after?: Map<string, Set<number>>, 'quoted'?: string, }",
+        );
+    }
+
+    #[test]
+    fn fields_are_split_at_top_level_commas() {
+        assert_eq!(
+            split_fields(" a: Record<string, Array<[number, string]>>, b: (x: number, y: number) => void, /* c, d */ \"e, f\": string, "),
+            ["a: Record<string, Array<[number, string]>>", "b: (x: number, y: number) => void", "/* c, d */ \"e, f\": string"],
+        );
+    }
+
+    #[test]
+    fn fields_are_sorted_by_name_ignoring_case_comments_and_quotes() {
+        let decl = "interface User { name: string, /* tags, by name */ tags: Record<string, Array<number>>, \"Age\"?: number, readonly id: number, }";
+
+        assert_eq!(
+            sort_fields(decl),
+            "interface User { \"Age\"?: number, readonly id: number, name: string, /* tags, by name */ tags: Record<string, Array<number>>, }",
+        );
+    }
+
+    #[test]
+    fn only_interfaces_are_sorted() {
+        let decl = "type Pair = { b: number, a: number, };";
+
+        assert_eq!(sort_fields(decl), decl);
+        assert_eq!(sort_fields("interface Empty { }"), "interface Empty { }");
+    }
+
+    #[test]
+    fn header_is_replaced_if_content_is_changed() {
+        let generated = "// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.\n\nexport interface User { name: string, }";
+        let content = generated.replace("name", "readonly name");
+
+        assert_eq!(replace_header(generated, generated), generated);
+        assert_eq!(replace_header(&content, generated), format!("{INDEX_HEADER}\n\nexport interface User {{ readonly name: string, }}"));
+    }
+
+    #[test]
+    fn readme_entries_are_replaced_and_sorted() {
+        let content = "<!-- header -->\n\n# Bindings\n\n## Commands\n\n### `ping`\n\n```ts\nping(): Promise<void>\n```\n\n### `greet`\n\nGreets the user\n\n```ts\ngreet(): Promise<string>\n```\n\n## Entities\n\n### `User`\n\n```ts\ninterface User { }\n```\n";
+        let entries = readme_entries(content);
+
+        assert_eq!(entries.keys().cloned().collect::<Vec<_>>(), [(0, "greet".to_owned()), (0, "ping".to_owned()), (1, "User".to_owned())]);
+        assert_eq!(entries[&(0, "greet".to_owned())], "### `greet`\n\nGreets the user\n\n```ts\ngreet(): Promise<string>\n```");
+    }
+
+    #[test]
+    fn imports_are_sorted_after_the_header() {
+        let content = "// header\nimport type { User } from \"./User\";\nimport type { Role } from \"./Role\";\n\nexport interface Team { role: Role, user: User, }";
+
+        assert_eq!(sort_imports(content), "// header\nimport type { Role } from \"./Role\";\nimport type { User } from \"./User\";\n\nexport interface Team { role: Role, user: User, }");
+    }
+
+    #[test]
+    fn flattened_types_are_intersected() {
+        let types = ["Meta".to_owned(), "Page<Record<string, number>>".to_owned()];
+
+        assert_eq!(
+            intersect("interface Response { data: Record<string, Array<number>>, }", &types),
+            "type Response = Meta & Page<Record<string, number>> & { data: Record<string, Array<number>>, };",
+        );
+        assert_eq!(intersect("interface Response { }", &types), "type Response = Meta & Page<Record<string, number>>;");
+        assert_eq!(
+            extend("interface Response<T> { data: T, }", &types[..1]),
+            "interface Response<T> extends Meta { data: T, }",
+        );
+    }
+
+    #[test]
+    fn newtypes_are_branded() {
+        assert_eq!(brand("type UserId = number;", "UserId"), "type UserId = number & { readonly __brand: \"UserId\" };");
+        assert_eq!(brand("type Tags = Record<string, Array<string>>;", "Tags"), "type Tags = Record<string, Array<string>> & { readonly __brand: \"Tags\" };");
+        assert_eq!(brand("type Name = string | null;", "Name"), "type Name = string & { readonly __brand: \"Name\" } | null;");
+        assert_eq!(brand("type Id = string | number;", "Id"), "type Id = (string | number) & { readonly __brand: \"Id\" };");
+    }
+}
@@ -3,6 +3,14 @@ pub use tauri_bindgen_ts_macro::*;
 pub use ts_rs;
 pub use serde;
 
+pub mod build;
+mod compat;
+pub mod duration;
+#[doc(hidden)]
+pub mod export;
+pub mod verify;
+pub mod workspace;
+
 pub mod prelude {
     pub use tauri_bindgen_ts_macro::*;
     pub use ts_rs;
@@ -0,0 +1,127 @@
+//! Checks that the generated bindings are up to date, i.e. so that CI fails if they were not regenerated and committed.
+//!
+//! ```no_run
+//! #[test]
+//! fn bindings_are_up_to_date() {
+//!     tauri_bindgen_ts::verify::assert_bindings_up_to_date("../src-gen");
+//! }
+//! ```
+//!
+//! The test needs to be a unit test of the crate that declares the bindings, as the tests generating them are only compiled into its own test binary.
+
+use std::{env, fs, path::{Component, Path}, process::Command};
+
+use crate::export;
+
+/// Filter that matches the names of all tests generated by the macros, i.e. `export_entity_bindings_user`
+const BINDING_TESTS: &str = "_bindings_";
+
+/// Runs the tests that generate the bindings again, with [`export::VERIFY_VAR`] set to `dir` so that nothing is written.
+/// Fails if a binding in `dir` is missing or differs from the file on disk, showing the difference.
+pub fn assert_bindings_up_to_date(dir: &str) {
+    // The tests run in the same binary, which calls this function again
+    if env::var_os(export::VERIFY_VAR).is_some() {
+        return;
+    }
+
+    let dir = env::current_dir().expect("Could not resolve the current directory").join(dir);
+    let exe = env::current_exe().expect("Could not resolve the test binary");
+    let output = Command::new(exe)
+        .arg(BINDING_TESTS)
+        .arg("--quiet")
+        .env(export::VERIFY_VAR, &dir)
+        .output()
+        .expect("Could not run the tests that generate the bindings");
+
+    if !output.status.success() {
+        panic!("The bindings in {} are not up to date, regenerate them with `cargo test`\n{}", dir.display(), String::from_utf8_lossy(&output.stdout));
+    }
+}
+
+/// Compares a binding to the file on disk if it is in `dir`, which is called instead of writing it while verifying
+pub(crate) fn check(file: &Path, content: &str, dir: &Path) {
+    let file = env::current_dir().map(|cwd| cwd.join(file)).unwrap_or_else(|_| file.to_owned());
+    if !normalize(&file).starts_with(&normalize(dir)) {
+        return;
+    }
+
+    match fs::read_to_string(&file) {
+        Ok(existing) if export::without_timestamp(&existing) == export::without_timestamp(content) => {}
+        Ok(existing) => panic!("{} is not up to date:\n{}", file.display(), diff(&existing, content)),
+        Err(_) => panic!("{} is missing", file.display()),
+    }
+}
+
+/// Components of the path with `..` resolved, as export directories are usually relative to the crate, i.e. "../src-gen"
+fn normalize(path: &Path) -> Vec<Component<'_>> {
+    let mut components = vec![];
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if matches!(components.last(), Some(Component::Normal(_))) => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+    components
+}
+
+/// Lines removed from `old` prefixed with `-` and lines added in `new` prefixed with `+`, along with unchanged lines prefixed with a space
+fn diff(old: &str, new: &str) -> String {
+    let (old, new) = (old.lines().collect::<Vec<_>>(), new.lines().collect::<Vec<_>>());
+    // Length of the longest common subsequence of the remaining lines, starting at each pair of lines
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = match old[i] == new[j] {
+                true => common[i + 1][j + 1] + 1,
+                false => common[i + 1][j].max(common[i][j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j, mut lines) = (0, 0, vec![]);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(format!("  {}", old[i]));
+            (i, j) = (i + 1, j + 1);
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            lines.push(format!("- {}", old[i]));
+            i += 1;
+        } else {
+            lines.push(format!("+ {}", new[j]));
+            j += 1;
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_shows_changed_lines() {
+        let old = "import type { User } from \"./User\";\n\nexport function getUser(id: number): Promise<User>";
+        let new = "import type { User } from \"./User\";\n\nexport function getUser(id: string): Promise<User>\nexport const x = 1";
+
+        assert_eq!(
+            diff(old, new),
+            "  import type { User } from \"./User\";\n  \n- export function getUser(id: number): Promise<User>\n+ export function getUser(id: string): Promise<User>\n+ export const x = 1",
+        );
+    }
+
+    #[test]
+    fn only_bindings_in_the_directory_are_compared() {
+        let dir = env::temp_dir().join("tauri-bindgen-ts-verify");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("User.ts"), "export interface User { name: string }").unwrap();
+
+        check(&dir.join("User.ts"), "export interface User { name: string }", &dir);
+        check(&dir.join("../Other.ts"), "export interface Other { }", &dir);
+        let missing = std::panic::catch_unwind(|| check(&dir.join("Missing.ts"), "", &dir));
+        let changed = std::panic::catch_unwind(|| check(&dir.join("User.ts"), "export interface User { id: number }", &dir));
+        assert!(missing.is_err() && changed.is_err());
+    }
+}
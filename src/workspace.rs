@@ -0,0 +1,119 @@
+//! Collect the bindings of all crates in a Cargo workspace into a single output directory.
+//!
+//! Each crate that uses [`build::generate_all`](crate::build::generate_all) writes descriptors of its bindings to its own `OUT_DIR`.
+//! Calling [`collect_all`] from the build script of another crate or from an `xtask` writes the bindings of all crates at once, with a single index file:
+//!
+//! ```no_run
+//! // in build.rs or xtask/src/main.rs
+//! tauri_bindgen_ts::workspace::collect_all("../frontend/src/bindings");
+//! ```
+//!
+//! Bindings are placed directly in the given directory, bindings of a ```module``` in its subdirectory. Command names therefore need to be unique across the workspace.
+//! Same as for [`build`](crate::build), entity types are exported by ts_rs and still require running `cargo test`.
+
+use std::{env, fs, path::{Path, PathBuf}, time::SystemTime};
+
+use crate::build::{self, Descriptor};
+use crate::export;
+
+/// Writes the bindings described by all crates of the workspace to `dir`, which is relative to the crate calling this function.
+/// Descriptors are looked up in the build directories of the target directory, which is `CARGO_TARGET_DIR` or the "target" directory of the workspace.
+/// Only the latest build of each crate is used, so that commands that were removed since do not come back from the build directory of an older build.
+/// If a binding was described in multiple builds, i.e. in both debug and release, the most recent descriptor is used.
+pub fn collect_all(dir: impl AsRef<Path>) {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").map(PathBuf::from).unwrap_or_default();
+    let dir = manifest_dir.join(dir);
+    let target_dir = target_dir(&manifest_dir).expect("Could not find the target directory, set CARGO_TARGET_DIR to specify it");
+
+    let mut bindings: Vec<(PathBuf, SystemTime, Descriptor)> = vec![];
+    for path in latest_descriptor_dirs(&target_dir).iter().flat_map(|dir| build::descriptor_paths(dir)) {
+        let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+        let descriptor = build::read_descriptor(&path);
+        let file = output_file(&dir, &descriptor);
+        match bindings.iter_mut().find(|(existing, ..)| *existing == file) {
+            Some(binding) if binding.1 < modified => *binding = (file, modified, descriptor),
+            Some(_) => {}
+            None => bindings.push((file, modified, descriptor)),
+        }
+    }
+    bindings.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+    for (file, _, descriptor) in bindings {
+        if descriptor.format.as_deref() == Some("prettier") {
+            export::use_prettier();
+        }
+        export::write(&file, &descriptor.binding());
+        if let (Some(parent), Some(name)) = (file.parent(), file.file_stem()) {
+            export::add_command_to_index(parent, &name.to_string_lossy());
+        }
+        if let Some(module) = descriptor.module {
+            export::add_module_to_index(&dir, &module.name);
+        }
+        // All commands of the workspace are listed in the single index file
+        if let Some(command) = descriptor.command {
+            export::add_command_to_list(&dir, &command.name);
+        }
+    }
+}
+
+/// Location of a binding in `dir`, which keeps the subdirectory of its module
+fn output_file(dir: &Path, descriptor: &Descriptor) -> PathBuf {
+    let name = descriptor.file.file_name().unwrap_or_default();
+    match &descriptor.module {
+        Some(module) => dir.join(&module.name).join(name),
+        None => dir.join(name),
+    }
+}
+
+/// The target directory is either set explicitly, contains the `OUT_DIR` of a build script, or is located in the workspace root
+fn target_dir(manifest_dir: &Path) -> Option<PathBuf> {
+    if let Some(target_dir) = env::var_os("CARGO_TARGET_DIR") {
+        return Some(manifest_dir.join(target_dir));
+    }
+    // `OUT_DIR` of a build script is "target/<profile>/build/<crate>-<hash>/out"
+    if let Some(out_dir) = env::var_os("OUT_DIR") {
+        return PathBuf::from(out_dir).ancestors().nth(4).map(Path::to_owned);
+    }
+
+    manifest_dir.ancestors().map(|dir| dir.join("target")).find(|dir| dir.is_dir())
+}
+
+/// Descriptor directory of the latest build of each crate, as cargo keeps the build directories of builds with different profiles, features or versions
+fn latest_descriptor_dirs(target_dir: &Path) -> Vec<PathBuf> {
+    // Crate name, name of the latest manifest and descriptor directory
+    let mut latest: Vec<(String, PathBuf, PathBuf)> = vec![];
+    for build_dir in build_dirs(target_dir) {
+        let dir = build_dir.join("out").join(build::DESCRIPTOR_DIR);
+        let Some((manifest, _)) = build::latest_manifest(&dir) else { continue };
+        let Some(manifest) = manifest.file_name().map(PathBuf::from) else { continue };
+        // Build directories are named "<crate>-<hash>"
+        let Some((name, _)) = build_dir.file_name().and_then(|name| name.to_str()).and_then(|name| name.rsplit_once('-')) else { continue };
+
+        match latest.iter_mut().find(|(existing, ..)| existing == name) {
+            Some(entry) if entry.1 < manifest => *entry = (name.to_owned(), manifest, dir),
+            Some(_) => {}
+            None => latest.push((name.to_owned(), manifest, dir)),
+        }
+    }
+
+    latest.into_iter().map(|(_, _, dir)| dir).collect()
+}
+
+/// Directories of build scripts of all profiles and targets, i.e. "target/debug/build/my-crate-1234"
+fn build_dirs(target_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    let mut pending = vec![target_dir.to_owned()];
+    // Builds for a specific target triple are nested, i.e. "target/x86_64-unknown-linux-gnu/debug/build"
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else { continue };
+        for entry in entries.flatten().filter(|entry| entry.path().is_dir()) {
+            match entry.file_name() == "build" {
+                true => dirs.extend(fs::read_dir(entry.path()).into_iter().flatten().flatten().map(|entry| entry.path())),
+                false if dir.strip_prefix(target_dir).map_or(0, |rel| rel.components().count()) < 2 => pending.push(entry.path()),
+                false => {}
+            }
+        }
+    }
+    dirs.sort();
+    dirs
+}
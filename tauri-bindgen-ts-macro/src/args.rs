@@ -0,0 +1,252 @@
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Ident, Lit, LitStr, Token};
+
+use crate::config;
+
+/// Arguments passed to an attribute, i.e. ```#[command("../src-gen", import_prefix = "./types")]```
+/// A plain string literal specifies the export directory, all other arguments are either flags, key-value pairs or lists of strings such as ```key = ["a", "b"]```.
+#[derive(Default)]
+pub(crate) struct Args {
+    dir: Option<LitStr>,
+    values: Vec<(Ident, Option<Lit>)>,
+    lists: Vec<(Ident, Vec<LitStr>)>,
+}
+
+enum Arg {
+    Dir(LitStr),
+    Value(Ident, Option<Lit>),
+    List(Ident, Vec<LitStr>),
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitStr) {
+            return Ok(Arg::Dir(input.parse()?));
+        }
+
+        let key = input.parse::<Ident>()?;
+        if input.parse::<Option<Token![=]>>()?.is_none() {
+            return Ok(Arg::Value(key, None));
+        }
+        if input.peek(syn::token::Bracket) {
+            let content;
+            syn::bracketed!(content in input);
+            let items = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+            return Ok(Arg::List(key, items.into_iter().collect()));
+        }
+
+        Ok(Arg::Value(key, Some(input.parse()?)))
+    }
+}
+
+impl Parse for Args {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Args { dir: None, values: vec![], lists: vec![] };
+        for arg in Punctuated::<Arg, Token![,]>::parse_terminated(input)? {
+            match arg {
+                Arg::Dir(dir) if args.dir.is_some() => return Err(syn::Error::new(dir.span(), "The export directory can only be specified once")),
+                Arg::Dir(dir) => args.dir = Some(dir),
+                Arg::Value(key, value) => args.values.push((key, value)),
+                Arg::List(key, items) => args.lists.push((key, items)),
+            }
+        }
+
+        Ok(args)
+    }
+}
+
+impl Args {
+    /// Fails if an argument is passed that is not contained in `known`
+    pub(crate) fn expect_keys(&self, known: &[&str]) -> syn::Result<()> {
+        match self.keys().find(|key| !known.iter().any(|known| *key == known)) {
+            Some(key) if known.is_empty() => Err(syn::Error::new(key.span(), format!("Unknown argument `{key}`, only the export directory can be specified"))),
+            Some(key) => Err(syn::Error::new(key.span(), format!("Unknown argument `{key}`, expected one of: {}", known.join(", ")))),
+            None => Ok(()),
+        }
+    }
+
+    /// The specified export dir. Defaults to the ```output_dir``` of the config or "../src-gen"
+    pub(crate) fn dir(&self) -> syn::Result<String> {
+        match &self.dir {
+            Some(dir) if !dir.value().is_empty() => validate_dir(dir),
+            _ => default_dir(),
+        }
+    }
+
+    /// The plain string argument, for attributes where it does not specify the export directory
+    pub(crate) fn literal(&self) -> Option<&LitStr> {
+        self.dir.as_ref()
+    }
+
+    /// The export dir specified by ```dir = "..."```, for attributes that use the plain string argument for something else. Defaults to the same as [Args::dir]
+    pub(crate) fn dir_key(&self) -> syn::Result<String> {
+        match self.scalar("dir")? {
+            Some(Some(Lit::Str(dir))) if !dir.value().is_empty() => validate_dir(dir),
+            Some(Some(Lit::Str(_))) | None => default_dir(),
+            Some(_) => Err(self.error("dir", "Expected a string value for `dir`, i.e. dir = \"../src-gen\"".to_owned())),
+        }
+    }
+
+    /// Export directories specified by a list such as ```export_to_multiple = ["../app/src/types", "../docs/gen"]```, which replace the plain string argument
+    pub(crate) fn dirs(&self, key: &str) -> syn::Result<Option<Vec<String>>> {
+        let Some((ident, dirs)) = self.lists.iter().find(|(ident, _)| ident == key) else {
+            return match self.value(key) {
+                Some(_) => Err(self.error(key, format!("Expected a list of directories for `{key}`, i.e. {key} = [\"../src-gen\", \"../docs/gen\"]"))),
+                None => Ok(None),
+            };
+        };
+        if let Some(dir) = &self.dir {
+            return Err(syn::Error::new(dir.span(), format!("The export directory can not be specified in addition to `{key}`")));
+        }
+        if dirs.is_empty() {
+            return Err(syn::Error::new(ident.span(), format!("`{key}` needs at least one directory")));
+        }
+
+        dirs.iter().map(validate_dir).collect::<syn::Result<Vec<_>>>().map(Some)
+    }
+
+    /// Subdirectory of the export dir specified by ```module = "models"```, without leading or trailing slashes
+    pub(crate) fn module(&self) -> syn::Result<Option<String>> {
+        let Some(module) = self.string("module")? else { return Ok(None) };
+        let module = module.trim_matches('/').to_owned();
+        let is_relative = Path::new(&module).components().all(|component| matches!(component, Component::Normal(_)));
+        if module.is_empty() || !is_relative || module.contains('\\') {
+            return Err(self.error("module", format!("Invalid module \"{module}\": Expected a relative path such as \"models\" or \"api/users\"")));
+        }
+
+        Ok(Some(module))
+    }
+
+    /// Value of a key-value argument such as ```rename = "MyName"```
+    pub(crate) fn string(&self, key: &str) -> syn::Result<Option<String>> {
+        match self.scalar(key)? {
+            Some(Some(Lit::Str(value))) => Ok(Some(value.value())),
+            Some(_) => Err(self.error(key, format!("Expected a string value for `{key}`, i.e. {key} = \"...\""))),
+            None => Ok(None),
+        }
+    }
+
+    /// Either a single string or a list of strings, i.e. ```acl = "fs:allow-read"``` or ```acl = ["fs:allow-read", "fs:allow-write"]```
+    pub(crate) fn strings(&self, key: &str) -> syn::Result<Vec<LitStr>> {
+        if let Some((_, items)) = self.lists.iter().find(|(ident, _)| ident == key) {
+            return Ok(items.clone());
+        }
+        match self.value(key) {
+            Some(Some(Lit::Str(value))) => Ok(vec![value.clone()]),
+            Some(_) => Err(self.error(key, format!("Expected a string or a list of strings for `{key}`, i.e. {key} = [\"...\"]"))),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Value of a key-value argument such as ```timeout_ms = 5000```
+    pub(crate) fn integer(&self, key: &str) -> syn::Result<Option<u64>> {
+        match self.scalar(key)? {
+            Some(Some(Lit::Int(value))) => Ok(Some(value.base10_parse()?)),
+            Some(_) => Err(self.error(key, format!("Expected an integer value for `{key}`, i.e. {key} = 1000"))),
+            None => Ok(None),
+        }
+    }
+
+    /// Whether a flag such as ```readonly_sets``` is present. Flags can also be set explicitly, i.e. ```readonly_sets = false```
+    pub(crate) fn flag(&self, key: &str) -> syn::Result<bool> {
+        Ok(self.optional_flag(key)?.unwrap_or(false))
+    }
+
+    /// Same as [Args::flag], but distinguishes ```key = false``` from not passing the flag at all
+    pub(crate) fn optional_flag(&self, key: &str) -> syn::Result<Option<bool>> {
+        match self.scalar(key)? {
+            Some(None) => Ok(Some(true)),
+            Some(Some(Lit::Bool(value))) => Ok(Some(value.value)),
+            Some(_) => Err(self.error(key, format!("`{key}` is a flag and does not take a value other than true or false"))),
+            None => Ok(None),
+        }
+    }
+
+    /// Span of the argument `key`, if it is present
+    pub(crate) fn span(&self, key: &str) -> Option<proc_macro2::Span> {
+        self.keys().find(|ident| *ident == key).map(Ident::span)
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &Ident> {
+        self.values.iter().map(|(ident, _)| ident).chain(self.lists.iter().map(|(ident, _)| ident))
+    }
+
+    /// Same as [Args::value], but fails if a list is passed for `key`
+    fn scalar(&self, key: &str) -> syn::Result<Option<&Option<Lit>>> {
+        match self.lists.iter().find(|(ident, _)| ident == key) {
+            Some((ident, _)) => Err(syn::Error::new(ident.span(), format!("`{key}` does not take a list"))),
+            None => Ok(self.value(key)),
+        }
+    }
+
+    fn value(&self, key: &str) -> Option<&Option<Lit>> {
+        self.values.iter().find(|(ident, _)| ident == key).map(|(_, value)| value)
+    }
+
+    fn error(&self, key: &str, message: String) -> syn::Error {
+        syn::Error::new(self.span(key).expect("Key should be present"), message)
+    }
+}
+
+/// Rejects export directories that are clearly invalid, as these would only fail once the bindings are written
+fn default_dir() -> syn::Result<String> {
+    match &config::config()?.output_dir {
+        Some(dir) => validate_dir(&LitStr::new(dir, Span::call_site())),
+        None => Ok("../src-gen".to_owned()),
+    }
+}
+
+fn validate_dir(dir: &LitStr) -> syn::Result<String> {
+    let value = dir.value();
+    let error = |message: &str| Err(syn::Error::new(dir.span(), format!("Invalid export directory \"{value}\": {message}")));
+
+    if value.contains('\0') {
+        return error("Path must not contain null bytes");
+    }
+
+    let bytes = value.as_bytes();
+    let has_drive_letter = bytes.len() >= 3 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'\\' | b'/');
+    let is_windows_absolute = has_drive_letter || value.starts_with("\\\\");
+    if is_windows_absolute && !cfg!(windows) {
+        return error("Absolute Windows paths can not be used on this platform");
+    }
+
+    if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+        let manifest_dir = PathBuf::from(manifest_dir);
+        let root = project_root(&manifest_dir);
+        if !normalize(&manifest_dir.join(&value)).starts_with(normalize(&root)) {
+            return error(&format!("Path must not point outside of the project at {}", root.display()));
+        }
+    }
+
+    Ok(value)
+}
+
+/// The project root is the closest directory containing a package.json, which is usually the root of the Tauri app.
+/// Falls back to the parent directory of the crate, as Tauri apps place their Rust code in the top-level directory "src-tauri".
+fn project_root(manifest_dir: &Path) -> PathBuf {
+    manifest_dir.ancestors()
+        .find(|dir| dir.join("package.json").is_file())
+        .or_else(|| manifest_dir.parent())
+        .unwrap_or(manifest_dir)
+        .to_path_buf()
+}
+
+/// Resolves `.` and `..` without accessing the file system, as the export directory might not exist yet
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => { normalized.pop(); }
+            component => normalized.push(component),
+        }
+    }
+
+    normalized
+}
@@ -0,0 +1,124 @@
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+use quote::{quote, format_ident};
+use syn::{Attribute, ImplItem, ImplItemMethod, Item, ItemMod, Visibility};
+
+use crate::args::{self, Args};
+use crate::{config, descriptor, func_names, jsdoc, CommandOptions};
+
+/// Options of a method in a ```#[command_group]```, which are its own if it is annotated with ```#[command]``` or ```#[plugin_command]```.
+/// Other methods are only commands if they are public, in which case they use the options of the group.
+pub(crate) fn method_options(method: &ImplItemMethod, group: &CommandOptions) -> syn::Result<Option<CommandOptions>> {
+    match method.attrs.iter().find(|attr| is_command(attr)) {
+        Some(attr) => command_options(attr).map(Some),
+        None if matches!(method.vis, Visibility::Public(_)) => Ok(Some(group.clone())),
+        None => Ok(None),
+    }
+}
+
+/// Whether the attribute is ```#[command]``` or ```#[plugin_command]```
+pub(crate) fn is_command(attr: &Attribute) -> bool {
+    is_attribute(attr, "command") || is_attribute(attr, "plugin_command")
+}
+
+fn command_options(attr: &Attribute) -> syn::Result<CommandOptions> {
+    match is_attribute(attr, "plugin_command") {
+        true => CommandOptions::from_plugin_args(&attribute_args(attr)?),
+        false => CommandOptions::from_args(&attribute_args(attr)?),
+    }
+}
+
+/// Creates a test that generates a file exporting all commands of the module as a single object, i.e. ```export const api = { getUser, setUser }```.
+/// Commands are collected from the functions annotated with ```#[command]``` or ```#[plugin_command]``` and the impl blocks annotated with ```#[command_group]```.
+pub(crate) fn generate_module_test(args: &Args, module: &ItemMod) -> syn::Result<proc_macro2::TokenStream> {
+    args.expect_keys(&["name"])?;
+    let dir = args.dir()?;
+    let name = args.string("name")?.unwrap_or_else(|| module.ident.to_string());
+    let Some((_, items)) = &module.content else {
+        return Err(syn::Error::new_spanned(module, "Commands can only be collected from inline modules"));
+    };
+
+    // Paths of the bindings relative to the generated file, keyed by the name of the command
+    let mut commands = vec![];
+    for item in items {
+        match item {
+            Item::Fn(func) => {
+                let Some(attr) = func.attrs.iter().find(|attr| is_command(attr)) else { continue };
+                let options = command_options(attr)?;
+                let (_, command) = func_names(&func.sig.ident, &func.attrs, &options)?;
+                commands.push((command.clone(), format!("{}/{command}", relative(&dir, &options.out_dir()))));
+            }
+            Item::Impl(group) => {
+                let Some(attr) = group.attrs.iter().find(|attr| is_attribute(attr, "command_group")) else { continue };
+                let options = CommandOptions::from_args(&attribute_args(attr)?)?;
+                let methods = group.items.iter().filter_map(|item| match item {
+                    ImplItem::Method(method) => Some(method),
+                    _ => None,
+                });
+                for method in methods {
+                    let Some(options) = method_options(method, &options)? else { continue };
+                    let (_, command) = func_names(&method.sig.ident, &method.attrs, &options)?;
+                    commands.push((command.clone(), format!("{}/{command}", relative(&dir, &options.out_dir()))));
+                }
+            }
+            _ => {}
+        }
+    }
+    commands.sort();
+
+    let header = config::header();
+    let imports = commands.iter().map(|(command, path)| format!("\nimport {{ {command} }} from \"{path}\"")).collect::<String>();
+    let names = commands.iter().map(|(command, _)| command.as_str()).collect::<Vec<_>>().join(", ");
+    let doc = jsdoc::jsdoc(&jsdoc::docs(&module.attrs));
+    let content = format!("{header}{imports}\n\n{doc}export const {name} = {{ {names} }}");
+
+    let file_name = format!("{dir}/{name}.ts");
+    descriptor::write(&format!("{name}.module"), &serde_json::json!({ "file": file_name, "content": content, "source": descriptor::source(&module.ident) }));
+    let test_fn = format_ident!("export_module_bindings_{}", module.ident);
+    let source = crate::source(module.ident.span());
+
+    let setup = config::setup();
+    Ok(quote! {
+        #[cfg(test)]
+        #[test]
+        fn #test_fn() {
+            #setup
+            tauri_bindgen_ts::export::write(#file_name, &tauri_bindgen_ts::export::add_metadata(#content, #source));
+            tauri_bindgen_ts::export::add_command_to_index(#dir, #name);
+        }
+    })
+}
+
+/// Whether the attribute is the given macro of this crate, i.e. ```#[command]``` or ```#[tauri_bindgen_ts::command]```
+fn is_attribute(attr: &Attribute, name: &str) -> bool {
+    attr.path.segments.last().is_some_and(|segment| segment.ident == name)
+}
+
+fn attribute_args(attr: &Attribute) -> syn::Result<Args> {
+    if attr.tokens.is_empty() {
+        return Ok(Args::default());
+    }
+
+    attr.parse_args()
+}
+
+/// Import path of the directory `to` relative to the directory `from`, i.e. `./models` or `../api`
+fn relative(from: &str, to: &str) -> String {
+    let base = env::var("CARGO_MANIFEST_DIR").map(PathBuf::from).unwrap_or_default();
+    let from = args::normalize(&base.join(from));
+    let to = args::normalize(&base.join(to));
+
+    let common = from.components().zip(to.components()).take_while(|(from, to)| from == to).count();
+    let parents = from.components().skip(common).map(|_| Component::ParentDir);
+    let path = parents.chain(to.components().skip(common)).collect::<PathBuf>();
+
+    match path.starts_with("..") {
+        true => path_string(&path),
+        false => format!("./{}", path_string(&path)).trim_end_matches('/').to_owned(),
+    }
+}
+
+fn path_string(path: &Path) -> String {
+    path.components().map(|component| component.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
@@ -0,0 +1,380 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use proc_macro2::Span;
+
+const CONFIG_FILE: &str = "tauri-bindgen.toml";
+
+const HEADER: &str = "// This file was generated by [tauri-bindgen-ts](https://github.com/antoniusnaumann/tauri-bindgen-ts). Do not edit this file manually.";
+
+/// Project-wide defaults, read from the closest `tauri-bindgen.toml` in the crate directory or one of its parents, i.e. the workspace root
+/// ```toml
+/// output_dir = "../src/bindings"
+/// tauri_version = 2
+/// generate_mocks = true
+/// generate_readme = true
+/// add_ts_nocheck = false
+/// invoke_import = "~/lib/tauri"
+/// invoke_name = "myInvoke"
+/// eslint_disable = ["@typescript-eslint/no-explicit-any", "import/no-cycle"]
+/// with_type_map = { "crate::serde_helpers::as_string" = "string" }
+/// format = "prettier"
+///
+/// [type_map]
+/// "i64" = "string"
+/// ```
+#[derive(Default)]
+pub(crate) struct Config {
+    /// Export directory relative to the directory of the crate, which replaces "../src-gen" as default
+    pub(crate) output_dir: Option<String>,
+    /// Major version of Tauri, which takes precedence over the "tauri-v2" feature if set
+    pub(crate) tauri_version: Option<u8>,
+    /// Whether to generate mocks for all commands, same as passing ```mock``` to each of them
+    pub(crate) generate_mocks: bool,
+    /// Whether to list all commands and entities with their signatures and docs in "BINDINGS.md" in the export directory
+    pub(crate) generate_readme: bool,
+    /// Whether to disable type checking of all entity files, same as passing ```ts_nocheck``` to each of them
+    pub(crate) add_ts_nocheck: bool,
+    /// Module from which command bindings import `invoke`, i.e. a wrapper with error handling
+    pub(crate) invoke_import: Option<String>,
+    /// Name of the function exported by `invoke_import`, if it is not called `invoke`
+    pub(crate) invoke_name: Option<String>,
+    /// ESLint rules that are disabled in all generated bindings, so that these do not need to be ignored by the ESLint config
+    pub(crate) eslint_disable: Vec<String>,
+    /// TypeScript types that replace the default mapping of primitives, i.e. `string` for `i64` if it is serialized as string
+    pub(crate) type_map: Vec<(String, String)>,
+    /// TypeScript types of fields serialized with the given module, i.e. `string` for ```#[serde(with = "crate::serde_helpers::as_string")]```
+    pub(crate) with_type_map: Vec<(String, String)>,
+    /// Whether to format the generated bindings with Prettier, enabled with ```format = "prettier"```
+    pub(crate) prettier: bool,
+    path: Option<PathBuf>,
+}
+
+impl Config {
+    pub(crate) fn tauri_v2(&self) -> bool {
+        self.tauri_version.map_or(cfg!(feature = "tauri-v2"), |version| version == 2)
+    }
+
+    /// Import statement of `invoke` in command bindings, i.e. ```import { invoke } from "@tauri-apps/api/core"```.
+    /// A custom function is imported as `invoke`, so that the bindings themselves do not change.
+    pub(crate) fn invoke_import(&self) -> String {
+        // Tauri v2 moved `invoke` from the `tauri` module to `core`, the signature of `invoke` itself did not change
+        let default = if self.tauri_v2() { "@tauri-apps/api/core" } else { "@tauri-apps/api/tauri" };
+        let module = self.invoke_import.as_deref().unwrap_or(default);
+        match self.invoke_name.as_deref() {
+            Some(name) if name != "invoke" => format!("import {{ {name} as invoke }} from \"{module}\""),
+            _ => format!("import {{ invoke }} from \"{module}\""),
+        }
+    }
+
+    /// Comment that disables the configured ESLint rules, i.e. ```/* eslint-disable import/no-cycle */```
+    pub(crate) fn eslint_disable(&self) -> Option<String> {
+        (!self.eslint_disable.is_empty()).then(|| format!("/* eslint-disable {} */", self.eslint_disable.join(", ")))
+    }
+
+    /// TypeScript type of the given primitive such as `i64`, if its mapping is overridden
+    pub(crate) fn mapped_type(&self, primitive: &str) -> Option<&str> {
+        self.type_map.iter().find(|(key, _)| key == primitive).map(|(_, ty)| ty.as_str())
+    }
+
+    /// TypeScript type of fields serialized with the given module, as specified by ```with_type_map```
+    pub(crate) fn with_type(&self, module: &str) -> Option<&str> {
+        self.with_type_map.iter().find(|(key, _)| key == module.trim()).map(|(_, ty)| ty.as_str())
+    }
+}
+
+/// The config is only read once, as macros of the same crate are expanded in the same process
+pub(crate) fn config() -> syn::Result<&'static Config> {
+    static CONFIG: OnceLock<Result<Config, String>> = OnceLock::new();
+
+    CONFIG.get_or_init(load).as_ref().map_err(|message| syn::Error::new(Span::call_site(), message))
+}
+
+/// Statements at the start of a generated test, which enable formatting if configured.
+/// Includes the config file as well, so that the bindings are regenerated when the config changes.
+/// Errors are ignored, as these are already reported when reading the options of the attribute.
+pub(crate) fn setup() -> proc_macro2::TokenStream {
+    let Ok(config) = config() else { return quote::quote! {} };
+    let format = config.prettier.then(|| quote::quote! { tauri_bindgen_ts::export::use_prettier(); });
+    let Some(path) = config.path.as_ref().and_then(|path| path.to_str()) else { return quote::quote! { #format } };
+
+    quote::quote! {
+        const _: &[u8] = include_bytes!(#path);
+        #format
+    }
+}
+
+/// First lines of a generated binding, followed by the configured ESLint comment if any.
+/// Errors are ignored, as these are already reported when reading the options of the attribute.
+pub(crate) fn header() -> String {
+    match config().ok().and_then(Config::eslint_disable) {
+        Some(eslint) => format!("{HEADER}\n{eslint}"),
+        None => HEADER.to_owned(),
+    }
+}
+
+fn load() -> Result<Config, String> {
+    let Some(manifest_dir) = env::var_os("CARGO_MANIFEST_DIR").map(PathBuf::from) else { return Ok(Config::default()) };
+    let Some(path) = manifest_dir.ancestors().map(|dir| dir.join(CONFIG_FILE)).find(|path| path.is_file()) else { return Ok(Config::default()) };
+    let content = fs::read_to_string(&path).map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+
+    let config = parse(&content, &manifest_dir, &path).map_err(|message| format!("Invalid config at {}: {message}", path.display()))?;
+
+    Ok(Config { path: Some(path), ..config })
+}
+
+fn parse(content: &str, manifest_dir: &Path, path: &Path) -> Result<Config, String> {
+    let mut config = Config::default();
+    let mut section = None;
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let error = |message: &str| Err(format!("{message} in line {}", index + 1));
+        if let Some(header) = line.strip_prefix('[') {
+            match header.split_once(']') {
+                Some((name, rest)) if name.trim() == "type_map" && is_empty(rest) => section = Some("type_map"),
+                Some((name, rest)) if name.trim() == "with_type_map" && is_empty(rest) => section = Some("with_type_map"),
+                _ => return error("Unknown section, expected [type_map] or [with_type_map]"),
+            }
+            continue;
+        }
+
+        let Some((key, value)) = key_value(line) else { return error("Expected `key = value`") };
+        if section == Some("type_map") {
+            let primitive = key;
+            if !PRIMITIVES.contains(&primitive.as_str()) {
+                return error(&format!("Unknown primitive `{primitive}` in [type_map], expected one of: {}", PRIMITIVES.join(", ")));
+            }
+            match typescript_type(value.trim()) {
+                Some(ty) => config.type_map.push((primitive, ty)),
+                None => return error(&format!("Expected a TypeScript type such as \"string\" for `{primitive}`")),
+            }
+            continue;
+        }
+        if section == Some("with_type_map") {
+            let module = key;
+            match typescript_type(value.trim()) {
+                Some(ty) => config.with_type_map.push((module, ty)),
+                None => return error(&format!("Expected a TypeScript type such as \"string\" for `{module}`")),
+            }
+            continue;
+        }
+
+        match (key.as_str(), value.trim()) {
+            ("output_dir", value) => {
+                let Some(dir) = string(value) else { return error("Expected a string value for `output_dir`") };
+                let depth = manifest_dir.components().count() - path.parent().map_or(0, |dir| dir.components().count());
+                config.output_dir = Some(format!("{}{}", "../".repeat(depth), dir.trim_end_matches('/')));
+            }
+            ("tauri_version", value) => match value.split('#').next().map(str::trim) {
+                Some("1") => config.tauri_version = Some(1),
+                Some("2") => config.tauri_version = Some(2),
+                _ => return error("Expected 1 or 2 for `tauri_version`"),
+            },
+            ("generate_mocks", value) => match boolean(value) {
+                Some(value) => config.generate_mocks = value,
+                None => return error("Expected true or false for `generate_mocks`"),
+            },
+            ("generate_readme", value) => match boolean(value) {
+                Some(value) => config.generate_readme = value,
+                None => return error("Expected true or false for `generate_readme`"),
+            },
+            ("add_ts_nocheck", value) => match boolean(value) {
+                Some(value) => config.add_ts_nocheck = value,
+                None => return error("Expected true or false for `add_ts_nocheck`"),
+            },
+            ("invoke_import", value) => match string(value) {
+                Some(module) if !module.is_empty() && !module.contains(['"', '\\', '\n']) => config.invoke_import = Some(module),
+                _ => return error("Expected a module path such as \"~/lib/tauri\" for `invoke_import`"),
+            },
+            ("invoke_name", value) => match string(value) {
+                Some(name) if crate::is_identifier(&name) => config.invoke_name = Some(name),
+                _ => return error("Expected the name of a function for `invoke_name`"),
+            },
+            ("eslint_disable", value) => match strings(value) {
+                Some(rules) if rules.iter().all(|rule| !rule.is_empty() && !rule.contains(['*', ',', '\n'])) => config.eslint_disable = rules,
+                _ => return error("Expected an array of rule names such as [\"import/no-cycle\"] for `eslint_disable`"),
+            },
+            ("with_type_map", value) => match table(value) {
+                Some(entries) if entries.iter().all(|(_, ty)| is_typescript_type(ty)) => config.with_type_map.extend(entries),
+                _ => return error("Expected an inline table such as { \"crate::as_string\" = \"string\" } for `with_type_map`"),
+            },
+            ("format", value) => match string(value).as_deref() {
+                Some("prettier") => config.prettier = true,
+                Some("none") => config.prettier = false,
+                _ => return error("Expected \"prettier\" or \"none\" for `format`"),
+            },
+            (key, _) => return error(&format!("Unknown key `{key}`, expected one of: add_ts_nocheck, eslint_disable, format, generate_mocks, generate_readme, invoke_import, invoke_name, output_dir, tauri_version, with_type_map")),
+        }
+    }
+
+    Ok(config)
+}
+
+/// Rust types whose TypeScript type can be overridden in the ```[type_map]``` section
+const PRIMITIVES: &[&str] = &["bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "String", "u8", "u16", "u32", "u64", "u128", "usize"];
+
+/// Only single-line strings are supported, which are either basic strings with escapes or literal strings without
+fn string(value: &str) -> Option<String> {
+    let (string, rest) = prefix_string(value)?;
+
+    is_empty(rest).then_some(string)
+}
+
+/// Splits the string at the start of `value` from the rest
+fn prefix_string(value: &str) -> Option<(String, &str)> {
+    match value.strip_prefix('\'') {
+        Some(literal) => literal.split_once('\'').map(|(string, rest)| (string.to_owned(), rest)),
+        None => {
+            let mut strings = serde_json::Deserializer::from_str(value).into_iter::<String>();
+            let string = strings.next()?.ok()?;
+            Some((string, &value[strings.byte_offset()..]))
+        }
+    }
+}
+
+/// A string containing a TypeScript type, which is inserted into the generated bindings as is
+fn typescript_type(value: &str) -> Option<String> {
+    string(value).filter(|ty| is_typescript_type(ty))
+}
+
+fn is_typescript_type(ty: &str) -> bool {
+    !ty.trim().is_empty() && !ty.contains('\n')
+}
+
+/// Splits `key = value` into the key, without quotes if it is quoted, and the rest after the equal sign
+fn key_value(line: &str) -> Option<(String, &str)> {
+    match line.starts_with(['"', '\'']) {
+        true => prefix_string(line).and_then(|(key, rest)| Some((key, rest.trim_start().strip_prefix('=')?))),
+        false => line.split_once('=').map(|(key, rest)| (key.trim().to_owned(), rest)),
+    }
+}
+
+/// Only single-line inline tables with string values are supported, i.e. ```{ "key" = "value", other = 'value' }```
+fn table(value: &str) -> Option<Vec<(String, String)>> {
+    let mut entries = vec![];
+    let mut content = value.strip_prefix('{')?.trim_start();
+    // Values may contain braces themselves, so the end of the table is only known after parsing its entries
+    while !content.starts_with('}') {
+        let (key, rest) = key_value(content)?;
+        let (value, rest) = prefix_string(rest.trim_start())?;
+        entries.push((key, value));
+
+        let rest = rest.trim_start();
+        content = match rest.strip_prefix(',') {
+            Some(rest) => rest.trim_start(),
+            None if rest.starts_with('}') => rest,
+            None => return None,
+        };
+    }
+
+    is_empty(&content[1..]).then_some(entries)
+}
+
+/// Only single-line arrays of basic strings are supported
+fn strings(value: &str) -> Option<Vec<String>> {
+    let mut arrays = serde_json::Deserializer::from_str(value).into_iter::<Vec<String>>();
+    let strings = arrays.next()?.ok()?;
+
+    is_empty(&value[arrays.byte_offset()..]).then_some(strings)
+}
+
+fn boolean(value: &str) -> Option<bool> {
+    match value.split('#').next().map(str::trim) {
+        Some("true") => Some(true),
+        Some("false") => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether there is nothing but whitespace or a comment
+fn is_empty(rest: &str) -> bool {
+    let rest = rest.trim();
+    rest.is_empty() || rest.starts_with('#')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_at_root(content: &str) -> Result<Config, String> {
+        parse(content, Path::new("/app/src-tauri"), Path::new("/app/src-tauri/tauri-bindgen.toml"))
+    }
+
+    #[test]
+    fn all_keys_are_parsed() {
+        let config = parse_at_root(r#"
+            # Defaults of the app
+            output_dir = "../src/bindings/"
+            tauri_version = 2 # Tauri v2
+            generate_mocks = true
+            generate_readme = true
+            add_ts_nocheck = false
+            invoke_import = '~/lib/tauri'
+            invoke_name = "myInvoke"
+            eslint_disable = ["@typescript-eslint/no-explicit-any", "import/no-cycle"]
+            with_type_map = { "crate::serde_helpers::as_string" = "string", other = 'number' }
+            format = "prettier"
+
+            [type_map] # Serialized as strings
+            "i64" = "string"
+            u128 = 'string'
+        "#).unwrap();
+
+        assert_eq!(config.output_dir.as_deref(), Some("../src/bindings"));
+        assert_eq!(config.tauri_version, Some(2));
+        assert!(config.generate_mocks && config.generate_readme && !config.add_ts_nocheck && config.prettier);
+        assert_eq!(config.invoke_import.as_deref(), Some("~/lib/tauri"));
+        assert_eq!(config.invoke_name.as_deref(), Some("myInvoke"));
+        assert_eq!(config.eslint_disable, ["@typescript-eslint/no-explicit-any", "import/no-cycle"]);
+        assert_eq!(config.mapped_type("u128"), Some("string"));
+        assert_eq!(config.mapped_type("i64"), Some("string"));
+        assert_eq!(config.with_type("crate::serde_helpers::as_string"), Some("string"));
+        assert_eq!(config.with_type("other"), Some("number"));
+    }
+
+    #[test]
+    fn output_dir_is_relative_to_the_crate() {
+        let config = parse("output_dir = \"src/bindings\"", Path::new("/app/src-tauri"), Path::new("/app/tauri-bindgen.toml")).unwrap();
+
+        assert_eq!(config.output_dir.as_deref(), Some("../src/bindings"));
+    }
+
+    #[test]
+    fn quoted_values_may_contain_comment_and_table_characters() {
+        let config = parse_at_root(r#"
+            invoke_import = "~/lib/#tauri" # comment with a "quote"
+            with_type_map = { "crate::as_map" = "{ [key: string]: number }" } # }
+        "#).unwrap();
+
+        assert_eq!(config.invoke_import.as_deref(), Some("~/lib/#tauri"));
+        assert_eq!(config.with_type("crate::as_map"), Some("{ [key: string]: number }"));
+    }
+
+    #[test]
+    fn sections_accept_quoted_keys_with_equal_signs() {
+        let config = parse_at_root(r#"
+            [with_type_map]
+            "crate::serde::as_string" = "string"
+            "crate::eq=" = "number"
+        "#).unwrap();
+
+        assert_eq!(config.with_type("crate::serde::as_string"), Some("string"));
+        assert_eq!(config.with_type("crate::eq="), Some("number"));
+    }
+
+    #[test]
+    fn invalid_lines_are_reported_with_their_number() {
+        assert_eq!(parse_at_root("\n\noutput = \"src\"").err().unwrap(), "Unknown key `output`, expected one of: add_ts_nocheck, eslint_disable, format, generate_mocks, generate_readme, invoke_import, invoke_name, output_dir, tauri_version, with_type_map in line 3");
+        assert_eq!(parse_at_root("tauri_version = 3").err().unwrap(), "Expected 1 or 2 for `tauri_version` in line 1");
+        assert_eq!(parse_at_root("[types]").err().unwrap(), "Unknown section, expected [type_map] or [with_type_map] in line 1");
+        assert_eq!(parse_at_root("[type_map]\n\"i65\" = \"string\"").err().unwrap().split(',').next().unwrap(), "Unknown primitive `i65` in [type_map]");
+        assert!(parse_at_root("output_dir = \"src\" extra").is_err());
+        assert!(parse_at_root("with_type_map = { a = \"string\" b = \"number\" }").is_err());
+    }
+}
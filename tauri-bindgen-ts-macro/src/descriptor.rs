@@ -0,0 +1,123 @@
+//! Descriptors allow generating bindings from a build script instead of a test, see `tauri_bindgen_ts::build`.
+//! When the annotated crate has a build script, cargo sets `OUT_DIR` during compilation and a descriptor for each binding is written to `$OUT_DIR/tauri-bindgen-ts/`.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, process};
+
+use syn::{Ident, Lit, Meta, MetaNameValue, NestedMeta, Type};
+
+use crate::{command_template, config, CommandOptions, Func};
+
+/// Name of the directory inside `OUT_DIR` that holds the descriptors
+const DESCRIPTOR_DIR: &str = "tauri-bindgen-ts";
+
+/// Extension of the files that list the descriptors written by a compilation
+const MANIFEST_EXTENSION: &str = "manifest";
+
+/// Subdirectory of the descriptors of entities, which are used to resolve the names of entities in command bindings
+const ENTITY_DIR: &str = "entities";
+
+/// Encloses the Rust name of an entity in a command binding, which the build script replaces with its TypeScript name
+const ENTITY_NAME: char = '\u{1}';
+
+/// Encloses the Rust name of an entity in an import path, which the build script replaces with the path from which the entity is imported
+const ENTITY_IMPORT: char = '\u{2}';
+
+/// Writes a descriptor for a command binding, if the annotated crate has a build script
+pub(crate) fn write_command(func: &Func, options: &CommandOptions) {
+    let Func { name, args, ret, err, stream, .. } = func;
+    let message = stream.as_ref().map(|(_, ty)| ty);
+
+    // The TypeScript names of entities are not known during expansion, as these may be renamed
+    let mut entities = args.iter().map(|(_, ty)| ty).chain([ret]).chain(message).flat_map(|ty| ty.entities()).map(crate::types::entity_name).collect::<Vec<_>>();
+    entities.sort();
+    entities.dedup();
+    let imports = entities.iter().map(|name| format!("\nimport type {{ {ENTITY_NAME}{name}{ENTITY_NAME} }} from \"{ENTITY_IMPORT}{name}{ENTITY_IMPORT}\"")).collect::<String>();
+    let entity = |ty: &Type| format!("{ENTITY_NAME}{}{ENTITY_NAME}", crate::types::entity_name(ty));
+    let payload = options.payload(&func.invoked_args());
+    let args = args.iter().map(|(ident, ty)| format!("{ident}: {}", ty.expand_with(&entity))).collect::<Vec<_>>().join(", ");
+
+    let content = command_template(func, options)
+        .replace("%0", &args)
+        .replace("%1", &payload)
+        .replace("%2", &ret.expand_with(&entity))
+        .replace("%3", &imports)
+        .replace("%4", &options.error_type.clone().or_else(|| err.as_ref().map(|err| err.expand_with(&entity))).unwrap_or_default())
+        .replace("%6", &message.map(|message| message.expand_with(&entity)).unwrap_or_default());
+
+    let module = options.module.as_ref().map(|module| serde_json::json!({ "dir": options.dir, "name": module }));
+    let command = serde_json::json!({ "dir": options.dir, "name": options.invoke_key(&func.invoke_name) });
+    // The build script evaluates these, as it is not known during expansion whether a `cfg` attribute is active
+    let cfg = func.cfg.iter().filter_map(|attr| match attr.parse_meta() {
+        Ok(Meta::List(list)) if list.nested.len() == 1 => predicate(&list.nested[0]),
+        _ => None,
+    }).collect::<Vec<_>>();
+    let descriptor = serde_json::json!({ "file": format!("{}/{name}.ts", options.out_dir()), "content": content, "module": module, "command": command, "cfg": cfg, "import_prefix": options.import_prefix, "source": source(&func.ident) });
+    write(name, &descriptor);
+}
+
+/// Location of the item with the given name, i.e. "src/lib.rs:12", which is only known while the macro is expanded
+pub(crate) fn source(ident: &Ident) -> Option<String> {
+    proc_macro::is_available().then(|| {
+        let span = ident.span().unwrap();
+        format!("{}:{}", span.file(), span.line())
+    })
+}
+
+/// Configuration predicate of a ```#[cfg(...)]``` attribute, i.e. ```{ "option": { "key": "feature", "value": "premium" } }``` for ```feature = "premium"```.
+/// Predicates that can not be represented are omitted, so that the binding is still generated.
+fn predicate(meta: &NestedMeta) -> Option<serde_json::Value> {
+    let NestedMeta::Meta(meta) = meta else { return None };
+    let key = meta.path().get_ident()?.to_string();
+    match meta {
+        Meta::Path(_) => Some(serde_json::json!({ "option": { "key": key } })),
+        Meta::NameValue(MetaNameValue { lit: Lit::Str(value), .. }) => Some(serde_json::json!({ "option": { "key": key, "value": value.value() } })),
+        Meta::List(list) if key == "not" && list.nested.len() == 1 => Some(serde_json::json!({ "not": predicate(&list.nested[0])? })),
+        Meta::List(list) if key == "all" || key == "any" => {
+            let predicates = list.nested.iter().map(predicate).collect::<Option<Vec<_>>>()?;
+            Some(serde_json::json!({ key: predicates }))
+        }
+        _ => None,
+    }
+}
+
+/// Writes the TypeScript name of an entity and the path from which it is imported if it specifies ```import_from```, so that command bindings use the same as in tests
+pub(crate) fn write_entity(ident: &Ident, name: &str, import_from: Option<&str>) {
+    let Some(out_dir) = env::var_os("OUT_DIR") else { return };
+    let dir = PathBuf::from(out_dir).join(DESCRIPTOR_DIR).join(ENTITY_DIR);
+    let entity = serde_json::json!({ "name": name, "import_from": import_from });
+
+    let _ = fs::create_dir_all(&dir).and_then(|_| fs::write(dir.join(format!("{ident}.json")), entity.to_string()));
+}
+
+/// Writes a descriptor with the given name, which needs to be unique among all bindings of the crate
+pub(crate) fn write(name: &str, descriptor: &serde_json::Value) {
+    let Some(out_dir) = env::var_os("OUT_DIR") else { return };
+    let mut descriptor = descriptor.clone();
+    if config::config().is_ok_and(|config| config.prettier) {
+        descriptor["format"] = serde_json::json!("prettier");
+    }
+    let dir = PathBuf::from(out_dir).join(DESCRIPTOR_DIR);
+
+    // Failing to write a descriptor should not fail the compilation, bindings can still be generated by running the tests
+    let _ = fs::create_dir_all(&dir)
+        .and_then(|_| fs::write(dir.join(format!("{name}.json")), descriptor.to_string()))
+        .and_then(|_| add_to_manifest(&dir, name));
+}
+
+/// Lists the descriptor in the manifest of this compilation, so that the build script can tell outdated descriptors apart, i.e. of removed commands
+fn add_to_manifest(dir: &Path, name: &str) -> io::Result<()> {
+    // All macros of a crate are expanded by the same process. Manifests are named by the start of the compilation, so that the latest one sorts last
+    static COMPILATION: OnceLock<String> = OnceLock::new();
+    let compilation = COMPILATION.get_or_init(|| {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos());
+        format!("{nanos:020}-{}", process::id())
+    });
+
+    let mut manifest = OpenOptions::new().create(true).append(true).open(dir.join(format!("{compilation}.{MANIFEST_EXTENSION}")))?;
+    writeln!(manifest, "{name}")
+}
@@ -0,0 +1,971 @@
+use quote::{quote, quote_spanned, format_ident, ToTokens};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{parse_quote, Attribute, Data, DataStruct, DeriveInput, Field, Fields, Ident, ItemType, LitStr, Meta, Token, Variant, Visibility};
+
+use crate::args::Args;
+use crate::types::{TsType, TypeOptions};
+use crate::{config, factory, guard, jsdoc, json_schema, serde_attr, types, zod};
+
+/// Options that can be passed to the entity attribute
+pub(crate) struct EntityOptions {
+    /// Directory to which the resulting file will be exported
+    pub(crate) dir: String,
+    /// Further directories to which the same files are exported
+    pub(crate) extra_dirs: Vec<String>,
+    /// Name of the TypeScript type, if it differs from the Rust type
+    pub(crate) rename: Option<String>,
+    /// Also generate a Zod schema for runtime validation
+    pub(crate) zod: bool,
+    /// Also generate a JSON Schema for consumers that are not written in TypeScript
+    pub(crate) json_schema: bool,
+    /// Subdirectory of `dir` to which the file is exported instead
+    pub(crate) module: Option<String>,
+    /// Mark all fields as `readonly`
+    pub(crate) all_readonly: bool,
+    /// Omit fields of type `Option<T>` that are `None` when serializing, so that they can be declared as optional field
+    pub(crate) optional_fields: bool,
+    /// Also generate a type guard function
+    pub(crate) guard: bool,
+    /// Also generate a function that creates the entity from the given fields and defaults for the others
+    pub(crate) factory: bool,
+    /// Also declare a type in which all fields are optional, i.e. for the body of a patch request
+    pub(crate) partial: bool,
+    /// Serialize `std::time::Duration` as number of milliseconds
+    pub(crate) duration_as_ms: bool,
+    /// Declare an enum without data as `const enum` instead of a string union
+    pub(crate) const_enum: bool,
+    /// Declare a newtype struct as branded type, so that it can not be mixed up with other newtypes of the same type
+    pub(crate) branded: bool,
+    /// Declare `NonZero*` integers as branded number, so that plain numbers can not be passed instead
+    pub(crate) branded_nonzero: bool,
+    /// TypeScript type of 128-bit integers, if it is specified with ```large_int```
+    pub(crate) large_int: Option<&'static str>,
+    /// Disable type checking of the generated file with `// @ts-nocheck`
+    pub(crate) ts_nocheck: bool,
+    /// Warn about private fields that are declared in the binding, i.e. to review which data the frontend receives
+    pub(crate) warn_private: bool,
+    /// Keep the fields in declaration order instead of sorting them by name
+    pub(crate) preserve_order: bool,
+    /// Declare structs with named fields as interface even if they have flattened fields, i.e. ```interface Response extends Meta { }```
+    pub(crate) always_interface: bool,
+    /// Declare structs with named fields as type alias of an object type, i.e. ```type User = { name: string }```
+    pub(crate) always_type: bool,
+    /// Path from which other bindings import the type, i.e. if it is exported by another crate
+    pub(crate) import_from: Option<String>,
+    /// Do not generate a file for this type, as it is declared elsewhere
+    pub(crate) no_export: bool,
+    /// Do not derive `Clone`, i.e. if a field can not be cloned
+    pub(crate) no_clone: bool,
+    /// Do not derive `Debug`, i.e. if a field does not implement it
+    pub(crate) no_debug: bool,
+}
+
+impl EntityOptions {
+    pub(crate) fn from_args(args: &Args) -> syn::Result<Self> {
+        args.expect_keys(&["all_readonly", "always_interface", "always_type", "branded", "branded_nonzero", "const_enum", "duration_as_ms", "export_to_multiple", "factory", "guard", "import_from", "json_schema", "large_int", "module", "no_clone", "no_debug", "no_export", "optional_fields", "partial", "preserve_order", "rename", "ts_nocheck", "warn_private", "zod"])?;
+        let config = config::config()?;
+        if cfg!(feature = "strict-null") && args.flag("optional_fields")? {
+            let span = args.span("optional_fields").expect("Argument should be present");
+            return Err(syn::Error::new(span, "`optional_fields` declares fields as `name?: T`, which is not available with the feature \"strict-null\""));
+        }
+        if args.flag("always_interface")? && args.flag("always_type")? {
+            let span = args.span("always_type").expect("Argument should be present");
+            return Err(syn::Error::new(span, "`always_interface` and `always_type` can not be combined"));
+        }
+
+        // The first directory is the export path of ts_rs, from which other bindings import the type
+        let (dir, extra_dirs) = match args.dirs("export_to_multiple")? {
+            Some(mut dirs) => (dirs.remove(0), dirs),
+            None => (args.dir()?, vec![]),
+        };
+
+        Ok(EntityOptions {
+            dir,
+            extra_dirs,
+            rename: args.string("rename")?,
+            zod: args.flag("zod")?,
+            json_schema: args.flag("json_schema")?,
+            module: args.module()?,
+            all_readonly: args.flag("all_readonly")?,
+            optional_fields: args.flag("optional_fields")?,
+            guard: args.flag("guard")?,
+            factory: args.flag("factory")?,
+            partial: args.flag("partial")?,
+            branded: args.flag("branded")?,
+            branded_nonzero: args.flag("branded_nonzero")?,
+            large_int: types::large_int(args)?,
+            const_enum: args.flag("const_enum")?,
+            duration_as_ms: args.flag("duration_as_ms")?,
+            // Entities can opt out of the project-wide default with `ts_nocheck = false`
+            ts_nocheck: args.optional_flag("ts_nocheck")?.unwrap_or(config.add_ts_nocheck),
+            warn_private: args.flag("warn_private")?,
+            preserve_order: args.flag("preserve_order")?,
+            always_interface: args.flag("always_interface")?,
+            always_type: args.flag("always_type")?,
+            import_from: args.string("import_from")?,
+            no_export: args.flag("no_export")?,
+            no_clone: args.flag("no_clone")?,
+            no_debug: args.flag("no_debug")?,
+        })
+    }
+
+    /// Directory to which the file is actually exported, which is the subdirectory for the module if one is specified
+    pub(crate) fn out_dir(&self) -> String {
+        self.out_dir_of(&self.dir)
+    }
+
+    pub(crate) fn type_options(&self) -> TypeOptions {
+        TypeOptions {
+            duration_as_ms: self.duration_as_ms,
+            branded_nonzero: self.branded_nonzero,
+            large_int: self.large_int,
+            ..TypeOptions::default()
+        }
+    }
+
+    fn out_dir_of(&self, dir: &str) -> String {
+        match &self.module {
+            Some(module) => format!("{dir}/{module}"),
+            None => dir.to_owned(),
+        }
+    }
+}
+
+/// Creates a test that exports the entity using ts_rs and adds it to the index file of the export directory
+pub(crate) fn generate_entity_test(input: &DeriveInput, options: &EntityOptions) -> syn::Result<proc_macro2::TokenStream> {
+    let ident = &input.ident;
+    let test_fn = format_ident!("export_entity_bindings_{}", ident.to_string().to_lowercase());
+    let source = crate::source(ident.span());
+    // Same as ts_rs, generic types are exported with unit as type argument
+    let generics = input.generics.type_params().map(|_| quote! { () });
+    let doc = jsdoc::jsdoc(&jsdoc::docs(&input.attrs));
+    let union = match string_union(input)? {
+        Some(union) => quote! { Some(#union) },
+        None => quote! { None::<&str> },
+    };
+    let readonly = match readonly_fields(input, options.all_readonly)? {
+        Some(fields) => quote! { Some(&[#(#fields),*][..]) },
+        None => quote! { None::<&[&str]> },
+    };
+    let optional = optional_fields(input)?;
+    // With the feature "strict-null", optional fields of type `Option<T>` may be null as well
+    let nullable = match cfg!(feature = "strict-null") {
+        true => skipped_options(input)?,
+        false => vec![],
+    };
+    let type_options = options.type_options();
+    let has_duration = fields(input).iter().any(|field| TsType::from_type(&field.ty, &type_options).has_duration());
+    let const_enum = match (options.const_enum, unit_variants(input)?) {
+        (true, Some(variants)) => {
+            let members = variants.iter().map(|(ident, name)| format!("{ident} = {name:?}")).collect::<Vec<_>>().join(", ");
+            quote! { let declaration = format!("const enum {name} {{ {} }}\n\nexport type {name}Value = `${{{name}}}`;", #members, name = Entity::name()); }
+        }
+        (true, None) => return Err(syn::Error::new_spanned(ident, "Only enums without data can be declared as const enum")),
+        (false, _) => quote! {},
+    };
+    let brand = match options.branded {
+        true if is_newtype(input) => quote! { let declaration = tauri_bindgen_ts::export::brand(&declaration, &Entity::name()); },
+        true => return Err(syn::Error::new_spanned(ident, "Only newtype structs such as `struct UserId(u64)` can be branded")),
+        false => quote! {},
+    };
+    let is_interface = matches!(&input.data, Data::Struct(DataStruct { fields: Fields::Named(_), .. }));
+    if options.always_interface && !is_interface {
+        return Err(syn::Error::new_spanned(ident, "Only structs with named fields can be declared as interface"));
+    }
+    if options.always_type && options.const_enum {
+        return Err(syn::Error::new_spanned(ident, "Const enums can not be declared as type alias"));
+    }
+    let alias = match options.always_type && is_interface {
+        true => quote! { let declaration = tauri_bindgen_ts::export::type_alias(&declaration); },
+        false => quote! {},
+    };
+    let flattened = flattened_types(input)?;
+    // Flattened fields are skipped by ts_rs, which would otherwise inline their fields
+    let flatten = match flattened.is_empty() {
+        true => quote! {},
+        false => {
+            let entities = flattened.iter().flat_map(TsType::entities);
+            let combine = match options.always_interface {
+                true => quote! { tauri_bindgen_ts::export::extend },
+                false => quote! { tauri_bindgen_ts::export::intersect },
+            };
+            quote! {
+                let declaration = #combine(&declaration, &[#(#flattened),*]);
+                let dependencies: Vec<Option<tauri_bindgen_ts::ts_rs::Dependency>> = vec![#(tauri_bindgen_ts::ts_rs::Dependency::from_ty::<#entities>()),*];
+                let dependencies = dependencies.into_iter().flatten().collect::<Vec<_>>();
+                let content = tauri_bindgen_ts::export::add_imports(&content, Entity::EXPORT_TO.expect("Entity should have an export path"), &dependencies);
+            }
+        }
+    };
+    let cfg = crate::cfg_attributes(&input.attrs);
+    // Lets commands call the guard of their error type, and lets other entities check that the guards and schemas they use are generated
+    let markers = [(options.guard, quote! { Guarded }), (options.factory, quote! { WithFactory }), (options.zod, quote! { HasZodSchema }), (options.json_schema, quote! { HasJsonSchema })];
+    let impls = markers.iter().filter(|(enabled, _)| *enabled).map(|(_, marker)| quote! {
+        #(#cfg)*
+        impl tauri_bindgen_ts::export::#marker for #ident {}
+    });
+    // Nested entities need to generate the same, as their guard or schema is imported
+    let referenced = fields(input).into_iter()
+        .filter(|field| !is_omitted(&field.attrs))
+        .flat_map(|field| TsType::from_type(&field.ty, &type_options).referenced_entities().into_iter().cloned().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let assertions = [(options.guard, quote! { assert_guarded }), (options.zod, quote! { assert_zod_schema }), (options.json_schema, quote! { assert_json_schema })]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .flat_map(|(_, assert)| referenced.iter().map(move |ty| quote_spanned! { ty.span() => tauri_bindgen_ts::export::#assert::<#ty>(); }))
+        .collect::<Vec<_>>();
+    let guarded = quote! {
+        #(#impls)*
+        #(#cfg)*
+        const _: () = { #(#assertions)* };
+    };
+    let guard = match options.guard {
+        true => {
+            let (guard, entities) = guard::guard(input, &type_options)?;
+            quote! {
+                let guarded: Vec<String> = vec![#(<#entities as tauri_bindgen_ts::ts_rs::TS>::name()),*];
+                let content = tauri_bindgen_ts::export::add_guard(&content, &#guard, &guarded);
+            }
+        }
+        false => quote! {},
+    };
+    let factory = match options.factory {
+        true => {
+            let factory = factory::factory(input, &type_options)?;
+            quote! {
+                let (factory, factories) = #factory;
+                let content = tauri_bindgen_ts::export::add_factory(&content, &factory, &factories);
+            }
+        }
+        false => quote! {},
+    };
+    let partial = options.partial.then(|| partial(input)).transpose()?;
+    let readme = config::config()?.generate_readme;
+    let docs = jsdoc::docs(&input.attrs).join("\n");
+    // Entities in modules are listed with their path, as their names may be the same as those in other modules
+    let qualified = readme.then(|| match &options.module {
+        Some(module) => quote! { let qualified = format!("{}/{}", #module, Entity::name()); },
+        None => quote! { let qualified = Entity::name(); },
+    });
+    let schema = options.zod.then(|| zod::schema(input, &type_options)).transpose()?;
+    let json_schema = options.json_schema.then(|| json_schema::schema(input, &type_options)).transpose()?;
+    // The same files are written to each export directory, which has its own index file
+    let exports = std::iter::once(&options.dir).chain(&options.extra_dirs).map(|root| {
+        let dir = options.out_dir_of(root);
+        let module = module_index(root, options.module.as_deref());
+        // Entity files only contain types unless a guard, factory or const enum is generated, which needs to be exported as value
+        let add_to_index = match options.guard || options.factory || options.const_enum {
+            true => quote! { tauri_bindgen_ts::export::add_guarded_entity_to_index(#dir, &Entity::name()); },
+            false => quote! { tauri_bindgen_ts::export::add_entity_to_index(#dir, &Entity::name()); },
+        };
+        let durations = has_duration.then(|| quote! { tauri_bindgen_ts::export::add_duration_helpers(#root); });
+        let schema = schema.as_ref().map(|schema| quote! {
+            tauri_bindgen_ts::export::write(format!("{}/{}.zod.ts", #dir, Entity::name()), &tauri_bindgen_ts::export::add_metadata(&#schema, #source));
+            tauri_bindgen_ts::export::add_schema_to_index(#dir, &Entity::name());
+        });
+        let json_schema = json_schema.as_ref().map(|schema| quote! { tauri_bindgen_ts::export::write_json_schema(#dir, &Entity::name(), &#schema); });
+        let readme = readme.then(|| quote! { tauri_bindgen_ts::export::add_entity_to_readme(#root, &qualified, &declaration, #docs); });
+
+        quote! {
+            tauri_bindgen_ts::export::write(format!("{}/{}", #dir, file_name), &content);
+            #add_to_index
+            #durations
+            #schema
+            #json_schema
+            #readme
+            #module
+        }
+    });
+    let sort = match options.preserve_order {
+        true => quote! {},
+        false => quote! { let declaration = tauri_bindgen_ts::export::sort_fields(&declaration); },
+    };
+    let nocheck = match options.ts_nocheck {
+        true => quote! { let content = format!("// @ts-nocheck\n{content}"); },
+        false => quote! {},
+    };
+    // The header is followed by the same ESLint comment as other bindings
+    let eslint = match config::config()?.eslint_disable() {
+        Some(eslint) => quote! { let content = content.replacen('\n', &format!("\n{}\n", #eslint), 1); },
+        None => quote! {},
+    };
+
+    // Implemented regardless of `cfg(test)`, as bindings of other crates import the type from this path
+    let import_from = match &options.import_from {
+        Some(path) => {
+            let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+            quote! {
+                #(#cfg)*
+                impl #impl_generics tauri_bindgen_ts::export::ImportFrom for #ident #ty_generics #where_clause {
+                    const IMPORT_FROM: &'static str = #path;
+                }
+            }
+        }
+        None => quote! {},
+    };
+    // ts_rs imports nested entities from their export path, which differs if they specify `import_from`
+    let params = input.generics.type_params().map(|param| param.ident.clone()).collect::<Vec<_>>();
+    let nested = fields(input).into_iter()
+        .filter(|field| !is_omitted(&field.attrs))
+        .flat_map(|field| TsType::from_type(&field.ty, &type_options).entities().into_iter().cloned().collect::<Vec<_>>())
+        .filter(|ty| !mentions(ty.to_token_stream(), &params))
+        .collect::<Vec<_>>();
+    let redirect = (!nested.is_empty()).then(|| quote! {
+        use tauri_bindgen_ts::export::{CustomImport, DefaultImport, ExportedName, NotExported};
+        let mut content = content;
+        #(
+            let probe = &tauri_bindgen_ts::export::Probe::<#nested>::default();
+            if let (Some(name), Some(path)) = (probe.exported_name(), probe.import_from()) {
+                content = tauri_bindgen_ts::export::redirect_import(&content, &name, path);
+            }
+        )*
+    });
+
+    if options.no_export {
+        return Ok(quote! {
+            #guarded
+            #import_from
+        });
+    }
+
+    let setup = config::setup();
+    Ok(quote! {
+        #guarded
+        #import_from
+
+        #(#cfg)*
+        #[cfg(test)]
+        #[test]
+        fn #test_fn() {
+            #setup
+            use tauri_bindgen_ts::ts_rs::TS;
+            type Entity = #ident<#(#generics),*>;
+
+            let generated = Entity::export_to_string().expect("Could not generate entity binding");
+            let content = generated.clone();
+            let decl = Entity::decl();
+            // ts_rs does not know all rename rules supported by serde, so string unions are declared from the serialized variant names
+            let declaration = match #union {
+                Some(union) => format!("type {} = {};", Entity::name(), union),
+                None => decl.clone(),
+            };
+            // ts_rs only recognizes `skip_serializing_if = "Option::is_none"`, but fields are missing with any other condition as well and may be omitted if they have a default
+            let optional: &[&str] = &[#(#optional),*];
+            let declaration = match optional.is_empty() {
+                true => declaration,
+                false => tauri_bindgen_ts::export::optional(&declaration, optional),
+            };
+            let nullable: &[&str] = &[#(#nullable),*];
+            let declaration = match nullable.is_empty() {
+                true => declaration,
+                false => tauri_bindgen_ts::export::nullable(&declaration, nullable),
+            };
+            let declaration = match #readonly {
+                Some(fields) => tauri_bindgen_ts::export::readonly(&declaration, fields),
+                None => declaration,
+            };
+            #sort
+            #const_enum
+            #flatten
+            #brand
+            #alias
+            let content = content.replacen(&decl, &declaration, 1);
+            // The declaration follows the header and imports generated by ts_rs
+            let content = content.replacen("\nexport ", &format!("\n{}export ", #doc), 1);
+            #partial
+            #guard
+            #redirect
+            #factory
+            let content = tauri_bindgen_ts::export::sort_imports(&content);
+            let content = tauri_bindgen_ts::export::replace_header(&content, &generated);
+            let content = tauri_bindgen_ts::export::add_metadata(&content, #source);
+            #eslint
+            #nocheck
+
+            let export_to = std::path::Path::new(Entity::EXPORT_TO.expect("Entity should have an export path"));
+            let file_name = export_to.file_name().and_then(|name| name.to_str()).expect("Export path should end with a file name");
+            #qualified
+            #(#exports)*
+        }
+    })
+}
+
+/// Appends ```type PartialUser = { [K in keyof User]?: User[K] }``` to the content of the entity file, which keeps the type parameters of generic entities
+fn partial(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    if !matches!(&input.data, Data::Struct(DataStruct { fields: Fields::Named(_), .. })) {
+        return Err(syn::Error::new_spanned(&input.ident, "Partial types can only be declared for structs with named fields"));
+    }
+
+    let params = input.generics.type_params().map(|param| param.ident.to_string()).collect::<Vec<_>>();
+    let params = match params.is_empty() {
+        true => String::new(),
+        false => format!("<{}>", params.join(", ")),
+    };
+    Ok(quote! {
+        let content = format!("{content}\n\nexport type Partial{name}{params} = {{ [K in keyof {name}{params}]?: {name}{params}[K] }};", name = Entity::name(), params = #params);
+    })
+}
+
+/// Whether the tokens contain one of the given identifiers, i.e. a type parameter
+fn mentions(tokens: proc_macro2::TokenStream, idents: &[Ident]) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(ident) => idents.contains(&ident),
+        proc_macro2::TokenTree::Group(group) => mentions(group.stream(), idents),
+        _ => false,
+    })
+}
+
+/// Creates a test that declares a type alias such as ```type UserId = u64``` as ```export type UserId = number``` and adds it to the index file of the export directory.
+/// Rust resolves the alias to the aliased type, so other bindings still use the aliased type instead of the alias.
+pub(crate) fn generate_alias(item: &ItemType, options: &EntityOptions) -> syn::Result<proc_macro2::TokenStream> {
+    if let Some(param) = item.generics.params.first() {
+        return Err(syn::Error::new_spanned(param, "Generic type aliases can not be exported"));
+    }
+
+    let dir = options.out_dir();
+    let root = &options.dir;
+    let module = module_index(root, options.module.as_deref());
+    let name = options.rename.clone().unwrap_or_else(|| item.ident.to_string());
+    let test_fn = format_ident!("export_alias_bindings_{}", item.ident.to_string().to_lowercase());
+    let source = crate::source(item.ident.span());
+    let file = format!("{dir}/{name}.ts");
+    let ty = TsType::from_type(&item.ty, &TypeOptions::default());
+    let entities = ty.entities();
+    let header = config::header();
+    let doc = jsdoc::jsdoc(&jsdoc::docs(&item.attrs));
+    let nocheck = match options.ts_nocheck {
+        true => quote! { let content = format!("// @ts-nocheck\n{content}"); },
+        false => quote! {},
+    };
+    let readme = config::config()?.generate_readme.then(|| {
+        let qualified = match &options.module {
+            Some(module) => format!("{module}/{name}"),
+            None => name.clone(),
+        };
+        let docs = jsdoc::docs(&item.attrs).join("\n");
+        quote! { tauri_bindgen_ts::export::add_entity_to_readme(#root, #qualified, &format!("type {} = {}", #name, ty), #docs); }
+    });
+
+    let cfg = crate::cfg_attributes(&item.attrs);
+
+    let setup = config::setup();
+    Ok(quote! {
+        #(#cfg)*
+        #[cfg(test)]
+        #[test]
+        fn #test_fn() {
+            #setup
+            let ty: String = #ty;
+            let content = format!("{}\n\n{}export type {} = {};\n", #header, #doc, #name, ty);
+            let dependencies: Vec<Option<tauri_bindgen_ts::ts_rs::Dependency>> = vec![#(tauri_bindgen_ts::ts_rs::Dependency::from_ty::<#entities>()),*];
+            let dependencies = dependencies.into_iter().flatten().collect::<Vec<_>>();
+            let content = tauri_bindgen_ts::export::add_imports(&content, #file, &dependencies);
+            let content = tauri_bindgen_ts::export::sort_imports(&content);
+            let content = tauri_bindgen_ts::export::add_metadata(&content, #source);
+            #nocheck
+
+            tauri_bindgen_ts::export::write(#file, &content);
+            tauri_bindgen_ts::export::add_entity_to_index(#dir, #name);
+            #readme
+            #module
+        }
+    })
+}
+
+/// Re-exports a module from the index file of the export directory, so that its bindings can still be imported from there
+pub(crate) fn module_index(dir: &str, module: Option<&str>) -> proc_macro2::TokenStream {
+    match module {
+        Some(module) => quote! { tauri_bindgen_ts::export::add_module_to_index(#dir, #module); },
+        None => quote! {},
+    }
+}
+
+/// Replaces ```#[bindgen_skip]``` on fields and variants with ```#[ts(skip)]```, which omits them from the binding without changing how they are serialized.
+/// Removes ```#[bindgen(...)]``` attributes, which are only read by this crate. Flattened fields of structs are skipped as well, as these are intersected with the generated interface instead.
+pub(crate) fn replace_attributes(input: &mut DeriveInput) {
+    if intersects(input) {
+        let Data::Struct(data) = &mut input.data else { unreachable!("Only structs are intersected") };
+        for field in data.fields.iter_mut().filter(|field| is_flattened(field)) {
+            field.attrs.push(parse_quote! { #[ts(skip)] });
+        }
+    }
+
+    let replace = |attrs: &mut Vec<Attribute>| {
+        attrs.retain(|attr| !attr.path.is_ident("bindgen"));
+        for attr in attrs.iter_mut().filter(|attr| is_skipped(attr)) {
+            *attr = parse_quote! { #[ts(skip)] };
+        }
+    };
+
+    match &mut input.data {
+        Data::Struct(data) => data.fields.iter_mut().for_each(|field| replace(&mut field.attrs)),
+        Data::Enum(data) => data.variants.iter_mut().for_each(|variant| {
+            replace(&mut variant.attrs);
+            variant.fields.iter_mut().for_each(|field| replace(&mut field.attrs));
+        }),
+        Data::Union(_) => {}
+    }
+}
+
+fn is_skipped(attr: &Attribute) -> bool {
+    attr.path.is_ident("bindgen_skip")
+}
+
+/// Traits that are derived in addition to those required for the binding, unless the entity opts out or already derives them itself
+pub(crate) fn derives(input: &DeriveInput, options: &EntityOptions) -> Vec<Ident> {
+    let derived = input.attrs.iter()
+        .filter(|attr| attr.path.is_ident("derive"))
+        .filter_map(|attr| attr.parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated).ok())
+        .flatten()
+        .filter_map(|path| path.segments.last().map(|segment| segment.ident.to_string()))
+        .collect::<Vec<_>>();
+
+    [("Clone", options.no_clone), ("Debug", options.no_debug)].into_iter()
+        .filter(|(name, opt_out)| !opt_out && !derived.iter().any(|derived| derived == name))
+        .map(|(name, _)| format_ident!("{name}"))
+        .collect()
+}
+
+/// Fields of type `PhantomData<T>` are zero-sized and carry no data, so they are skipped by serde and omitted from the binding
+pub(crate) fn skip_phantom_data(input: &mut DeriveInput) {
+    let fields: Vec<&mut Field> = match &mut input.data {
+        Data::Struct(data) => data.fields.iter_mut().collect(),
+        Data::Enum(data) => data.variants.iter_mut().flat_map(|variant| variant.fields.iter_mut()).collect(),
+        Data::Union(_) => vec![],
+    };
+
+    for field in fields.into_iter().filter(|field| types::is_phantom_data(&field.ty)) {
+        if !serde_attr::flag(&field.attrs, "skip") {
+            field.attrs.push(parse_quote! { #[serde(skip)] });
+        }
+        field.attrs.push(parse_quote! { #[ts(skip)] });
+    }
+}
+
+/// ts_rs does not implement `TS` for `std::time::Duration` and the `NonZero*` integers, so the type of fields containing these is specified explicitly.
+/// With `duration_as_ms`, fields of type `Duration` and `Option<Duration>` are serialized as number of milliseconds instead.
+pub(crate) fn explicit_types(input: &mut DeriveInput, options: &TypeOptions) -> syn::Result<()> {
+    let as_ms = options.duration_as_ms;
+    let fields: Vec<&mut Field> = match &mut input.data {
+        Data::Struct(data) => data.fields.iter_mut().collect(),
+        Data::Enum(data) => data.variants.iter_mut().flat_map(|variant| variant.fields.iter_mut()).collect(),
+        Data::Union(_) => vec![],
+    };
+
+    let config = config::config()?;
+    for field in fields {
+        // Types specified by the entity itself are passed to ts_rs as they are
+        let has_ts_type = has_ts_type(&field.attrs);
+        // The serialized type of a field with `#[serde(with = "...")]` is only known if it is configured
+        if let Some(ty) = serde_attr::value(&field.attrs, "with").and_then(|with| config.with_type(&with.value())) {
+            if !has_ts_type {
+                field.attrs.push(parse_quote! { #[ts(type = #ty)] });
+            }
+            continue;
+        }
+
+        let is_option_duration = types::option_inner(&field.ty).is_some_and(types::is_duration);
+        let with = match () {
+            _ if !as_ms => None,
+            _ if types::is_duration(&field.ty) => Some("tauri_bindgen_ts::duration::millis"),
+            _ if is_option_duration => Some("tauri_bindgen_ts::duration::millis::option"),
+            _ if TsType::from_type(&field.ty, &TypeOptions::default()).has_duration() => {
+                return Err(syn::Error::new_spanned(&field.ty, "Only fields of type `Duration` or `Option<Duration>` can be serialized as milliseconds"));
+            }
+            _ => None,
+        };
+        if let Some(with) = with {
+            field.attrs.push(parse_quote! { #[serde(with = #with)] });
+        }
+
+        let ty = TsType::from_type(&field.ty, options);
+        // ts_rs uses its own mapping of primitives, so overridden ones and 128-bit integers are specified explicitly as well
+        let has_primitive = types::has_nonzero(&field.ty) || types::has_mapped_primitive(&field.ty) || types::has_large_int(&field.ty);
+        if has_primitive && !ty.entities().is_empty() && !has_ts_type {
+            return Err(syn::Error::new_spanned(&field.ty, "`NonZero*` integers, 128-bit integers and primitives of the [type_map] in the config can not be combined with other entities in the type of a single field"));
+        }
+        // Entities would not be imported when specifying the type explicitly, so these still require a `TS` implementation for `Duration`
+        if (with.is_some() || ty.has_duration() || has_primitive) && ty.entities().is_empty() && !has_ts_type {
+            let ty = ty.expand();
+            field.attrs.push(parse_quote! { #[ts(type = #ty)] });
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the type of the field is specified for ts_rs, i.e. ```#[ts(type = "string")]```
+fn has_ts_type(attrs: &[Attribute]) -> bool {
+    attrs.iter().filter(|attr| attr.path.is_ident("ts")).any(|attr| match attr.parse_meta() {
+        Ok(Meta::List(list)) => list.nested.iter().any(|nested| matches!(nested, syn::NestedMeta::Meta(Meta::NameValue(meta)) if meta.path.is_ident("type"))),
+        _ => false,
+    })
+}
+
+/// Fields of a struct or of all variants of an enum
+fn fields(input: &DeriveInput) -> Vec<&Field> {
+    match &input.data {
+        Data::Struct(data) => data.fields.iter().collect(),
+        Data::Enum(data) => data.variants.iter().flat_map(|variant| &variant.fields).collect(),
+        Data::Union(_) => vec![],
+    }
+}
+
+/// Skips serializing `None` for all fields of type `Option<T>`, which ts_rs declares as optional field, i.e. ```middle_name?: string```.
+/// As serde treats missing fields of type `Option<T>` as `None` when deserializing, values can be passed from the frontend the same way.
+pub(crate) fn make_optional(input: &mut DeriveInput) {
+    let Data::Struct(data) = &mut input.data else { return };
+    let fields = data.fields.iter_mut().filter(|field| types::is_option(&field.ty) && serde_attr::value(&field.attrs, "skip_serializing_if").is_none());
+    for field in fields {
+        field.attrs.push(parse_quote! { #[serde(skip_serializing_if = "Option::is_none")] });
+    }
+}
+
+/// Renames fields and variants for ts_rs when serde uses a rename rule that ts_rs does not support, i.e. ```#[serde(rename_all = "kebab-case")]```.
+/// Other rules such as ```camelCase``` are already applied by ts_rs, so that the binding uses the same keys as the serialized value.
+/// ts_rs ignores rename rules of variants entirely, so fields of a variant with ```#[serde(rename_all = "...")]``` are always renamed.
+pub(crate) fn rename_fields(input: &mut DeriveInput) -> syn::Result<()> {
+    let rule = serde_attr::value(&input.attrs, "rename_all").filter(|rule| ["kebab-case", "SCREAMING-KEBAB-CASE"].contains(&rule.value().as_str()));
+    let is_renamed = |attrs: &[Attribute]| serde_attr::value(attrs, "rename").is_some();
+    match &mut input.data {
+        Data::Struct(data) => {
+            let Some(rule) = rule else { return Ok(()) };
+            for field in data.fields.iter_mut().filter(|field| field.ident.is_some() && !is_renamed(&field.attrs)) {
+                let name = field_name(field, Some(&rule))?;
+                field.attrs.push(parse_quote! { #[ts(rename = #name)] });
+            }
+        }
+        Data::Enum(data) => for variant in data.variants.iter_mut() {
+            if let Some(rule) = rule.as_ref().filter(|_| !is_renamed(&variant.attrs)) {
+                let name = variant_name(variant, Some(rule))?;
+                variant.attrs.push(parse_quote! { #[ts(rename = #name)] });
+            }
+            let Some(rule) = serde_attr::value(&variant.attrs, "rename_all") else { continue };
+            for field in variant.fields.iter_mut().filter(|field| field.ident.is_some() && !is_renamed(&field.attrs)) {
+                let name = field_name(field, Some(&rule))?;
+                field.attrs.push(parse_quote! { #[ts(rename = #name)] });
+            }
+        },
+        Data::Union(_) => {}
+    }
+
+    Ok(())
+}
+
+/// ts_rs ignores a serde attribute entirely if it contains a key that ts_rs does not know, such as `deny_unknown_fields` in ```#[serde(tag = "type", deny_unknown_fields)]```.
+/// Splitting it into one attribute per key, which is equivalent for serde, lets ts_rs still pick up the keys it knows, such as the tag of an enum.
+pub(crate) fn split_serde_attributes(input: &mut DeriveInput) {
+    let split = |attrs: &mut Vec<Attribute>| {
+        *attrs = attrs.drain(..).flat_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) if serde_attr::is_serde(&attr) && list.nested.len() > 1 => {
+                list.nested.into_iter().map(|nested| parse_quote! { #[serde(#nested)] }).collect()
+            }
+            _ => vec![attr],
+        }).collect();
+    };
+
+    split(&mut input.attrs);
+    match &mut input.data {
+        Data::Struct(data) => data.fields.iter_mut().for_each(|field| split(&mut field.attrs)),
+        Data::Enum(data) => data.variants.iter_mut().for_each(|variant| {
+            split(&mut variant.attrs);
+            variant.fields.iter_mut().for_each(|field| split(&mut field.attrs));
+        }),
+        Data::Union(_) => {}
+    }
+}
+
+/// Options passed to a field with ```#[bindgen(...)]```, i.e. ```#[bindgen(readonly)]```
+fn field_options(field: &Field) -> syn::Result<Vec<Ident>> {
+    let mut options = vec![];
+    for attr in field.attrs.iter().filter(|attr| attr.path.is_ident("bindgen")) {
+        for option in attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)? {
+            if option != "readonly" {
+                return Err(syn::Error::new(option.span(), format!("Unknown option `{option}`, expected one of: readonly")));
+            }
+            options.push(option);
+        }
+    }
+
+    Ok(options)
+}
+
+/// Types of the fields of a struct that are annotated with ```#[serde(flatten)]```
+fn flattened_types(input: &DeriveInput) -> syn::Result<Vec<TsType>> {
+    let Data::Struct(DataStruct { fields: Fields::Named(fields), .. }) = &input.data else { return Ok(vec![]) };
+    if !intersects(input) {
+        return Ok(vec![]);
+    }
+    let flattened = fields.named.iter().filter(|field| is_flattened(field)).collect::<Vec<_>>();
+    if let (Some(field), Some(param)) = (flattened.first(), input.generics.type_params().next()) {
+        return Err(syn::Error::new_spanned(param, format!("Flattened fields such as `{}` are not supported in generic entities", field.ident.as_ref().expect("Field should be named"))));
+    }
+
+    Ok(flattened.iter().map(|field| TsType::from_type(&field.ty, &TypeOptions::default())).collect())
+}
+
+fn is_newtype(input: &DeriveInput) -> bool {
+    matches!(&input.data, Data::Struct(DataStruct { fields: Fields::Unnamed(fields), .. }) if fields.unnamed.len() == 1)
+}
+
+/// Whether the struct has both flattened fields and other fields. ts_rs fails for structs without fields, so these still inline the fields of flattened types.
+fn intersects(input: &DeriveInput) -> bool {
+    let Data::Struct(DataStruct { fields: Fields::Named(fields), .. }) = &input.data else { return false };
+    let fields = fields.named.iter().filter(|field| !is_omitted(&field.attrs));
+
+    fields.clone().any(is_flattened) && !fields.clone().all(is_flattened)
+}
+
+fn is_flattened(field: &Field) -> bool {
+    !is_omitted(&field.attrs) && serde_attr::flag(&field.attrs, "flatten")
+}
+
+/// Serialized names of the fields of a struct that may be missing, which ts_rs does not declare as optional.
+/// These are skipped when serializing with a condition other than ```Option::is_none```, or have a default value, i.e. ```#[serde(default)]``` on the field or the struct.
+fn optional_fields(input: &DeriveInput) -> syn::Result<Vec<String>> {
+    let Data::Struct(DataStruct { fields: Fields::Named(fields), .. }) = &input.data else { return Ok(vec![]) };
+    let rule = serde_attr::value(&input.attrs, "rename_all");
+    let has_default = |attrs: &[Attribute]| serde_attr::flag(attrs, "default") || serde_attr::value(attrs, "default").is_some();
+
+    fields.named.iter()
+        .filter(|field| !is_omitted(&field.attrs) && !serde_attr::flag(&field.attrs, "flatten"))
+        .filter(|field| {
+            let is_skipped = serde_attr::value(&field.attrs, "skip_serializing_if").is_some_and(|condition| condition.value() != "Option::is_none");
+            is_skipped || has_default(&field.attrs) || has_default(&input.attrs)
+        })
+        .map(|field| field_name(field, rule.as_ref()))
+        .collect()
+}
+
+/// Serialized names of the fields of type `Option<T>` that ts_rs declares as ```name?: T```, as they are skipped with ```Option::is_none```
+fn skipped_options(input: &DeriveInput) -> syn::Result<Vec<String>> {
+    let Data::Struct(DataStruct { fields: Fields::Named(fields), .. }) = &input.data else { return Ok(vec![]) };
+    let rule = serde_attr::value(&input.attrs, "rename_all");
+
+    fields.named.iter()
+        .filter(|field| !is_omitted(&field.attrs) && types::is_option(&field.ty))
+        .filter(|field| serde_attr::value(&field.attrs, "skip_serializing_if").is_some_and(|condition| condition.value() == "Option::is_none"))
+        .map(|field| field_name(field, rule.as_ref()))
+        .collect()
+}
+
+/// Serialized names of the fields that are marked with ```#[bindgen(readonly)]```, or of all fields if `all` is set.
+/// Returns `None` if no field is readonly.
+fn readonly_fields(input: &DeriveInput, all: bool) -> syn::Result<Option<Vec<String>>> {
+    let is_interface = matches!(&input.data, Data::Struct(data) if matches!(data.fields, Fields::Named(_)));
+    if all && !is_interface {
+        return Err(syn::Error::new_spanned(&input.ident, "Only structs with named fields can be readonly"));
+    }
+
+    let rule = serde_attr::value(&input.attrs, "rename_all");
+    let mut names = vec![];
+    for field in fields(input) {
+        let options = field_options(field)?;
+        match options.first() {
+            Some(option) if !is_interface => return Err(syn::Error::new(option.span(), "Only fields of structs with named fields can be readonly")),
+            Some(_) => names.push(field_name(field, rule.as_ref())?),
+            None if all => names.push(field_name(field, rule.as_ref())?),
+            None => {}
+        }
+    }
+
+    Ok(if names.is_empty() { None } else { Some(names) })
+}
+
+/// Rejects representations for which serde would produce invalid JSON
+pub(crate) fn validate(input: &DeriveInput) -> syn::Result<()> {
+    let Data::Enum(data) = &input.data else { return Ok(()) };
+    let Some(tag) = serde_attr::value(&input.attrs, "tag") else { return Ok(()) };
+    // Adjacently tagged enums place the fields into the content and can therefore not collide with the tag
+    if serde_attr::value(&input.attrs, "content").is_some() {
+        return Ok(());
+    }
+
+    // Internally tagged variants are serialized as object, so their content needs to be an object as well
+    for variant in data.variants.iter().filter(|variant| !is_omitted(&variant.attrs)) {
+        let Fields::Unnamed(fields) = &variant.fields else { continue };
+        let is_object = fields.unnamed.len() == 1 && matches!(TsType::from_type(&fields.unnamed[0].ty, &TypeOptions::default()), TsType::Entity(_) | TsType::Generic(..) | TsType::Record(..) | TsType::Custom(_) | TsType::Builtin("unknown"));
+        if !is_object {
+            return Err(syn::Error::new_spanned(variant, format!("Variant can not be serialized with the tag \"{}\", as its content is not an object. Use `#[serde(tag = \"...\", content = \"...\")]` instead", tag.value())));
+        }
+    }
+
+    let fields = data.variants.iter().flat_map(|variant| match &variant.fields {
+        Fields::Named(fields) => fields.named.iter().collect(),
+        _ => vec![],
+    });
+    for field in fields {
+        let name = serde_attr::value(&field.attrs, "rename").map(|rename| rename.value())
+            .or_else(|| field.ident.as_ref().map(|ident| ident.to_string()));
+        if name.as_deref() == Some(tag.value().as_str()) {
+            return Err(syn::Error::new_spanned(field, format!("Field conflicts with the tag \"{}\" of this enum", tag.value())));
+        }
+    }
+
+    Ok(())
+}
+
+/// 128-bit integers are declared as `string`, but serde serializes them as JSON number by default, which JavaScript parses into a `number` that loses precision.
+/// Fields that specify how they are serialized, i.e. ```#[serde(with = "...")]```, are assumed to be serialized as string, and no warning is emitted if ```large_int``` is specified.
+pub(crate) fn large_int_warnings(input: &DeriveInput, options: &EntityOptions) -> proc_macro2::TokenStream {
+    if options.large_int.is_some() {
+        return quote! {};
+    }
+
+    let cfg = crate::cfg_attributes(&input.attrs);
+    let types = fields(input).into_iter()
+        .filter(|field| ["with", "serialize_with"].iter().all(|key| serde_attr::value(&field.attrs, key).is_none()))
+        .map(|field| &field.ty)
+        .collect::<Vec<_>>();
+
+    large_int_warning(&types, &cfg)
+}
+
+/// Warns about each of the given types that contains a 128-bit integer, which is declared as `string` in TypeScript
+pub(crate) fn large_int_warning(types: &[&syn::Type], cfg: &[Attribute]) -> proc_macro2::TokenStream {
+    let warnings = types.iter().filter(|ty| types::has_large_int(ty)).map(|ty| {
+        let note = format!("`{}` contains a 128-bit integer, which is declared as `string` in TypeScript as a JavaScript number can not represent it. Make sure that serde serializes it as string, i.e. with serde_with::DisplayFromStr, and pass large_int = \"string\" or large_int = \"bigint\"", ty.to_token_stream().to_string().replace(" :: ", "::").replace(" < ", "<").replace(" >", ">"));
+        let warning = crate::warning(ty.span(), &note);
+        quote! { #(#cfg)* #warning }
+    });
+
+    quote! { #(#warnings)* }
+}
+
+/// Serde serializes private fields as well, so these are part of the binding even though other crates can not access them, which is only reported with ```warn_private```.
+/// Fields omitted from the binding, i.e. with ```#[serde(skip)]``` or ```#[bindgen_skip]```, as well as fields with a restricted visibility such as `pub(crate)` are not reported.
+pub(crate) fn private_field_warnings(input: &DeriveInput, options: &EntityOptions) -> proc_macro2::TokenStream {
+    let Data::Struct(data) = &input.data else { return quote! {} };
+    if !options.warn_private {
+        return quote! {};
+    }
+
+    let cfg = crate::cfg_attributes(&input.attrs);
+    let warnings = data.fields.iter().enumerate()
+        .filter(|(_, field)| matches!(field.vis, Visibility::Inherited) && !is_omitted(&field.attrs))
+        .map(|(index, field)| {
+            let name = field.ident.as_ref().map_or_else(|| index.to_string(), Ident::to_string);
+            let note = format!("Field `{name}` of `{}` is private, but serde serializes it, so it is declared in the binding. Skip it with #[serde(skip)] or #[bindgen_skip] if the frontend should not see it, or declare it as `pub` or `pub(crate)`", input.ident);
+            let warning = crate::warning(field.span(), &note);
+            quote! { #(#cfg)* #warning }
+        });
+
+    quote! { #(#warnings)* }
+}
+
+/// Untagged enums are deserialized into the first variant that matches, so variants with the same shape can never be told apart.
+/// The warning is raised at the span of the ambiguous variant.
+pub(crate) fn ambiguity_warnings(input: &DeriveInput) -> proc_macro2::TokenStream {
+    let Data::Enum(data) = &input.data else { return quote! {} };
+    if !serde_attr::flag(&input.attrs, "untagged") {
+        return quote! {};
+    }
+
+    let shape = |fields: &Fields| match fields {
+        Fields::Unit => "null".to_owned(),
+        Fields::Unnamed(fields) => fields.unnamed.iter().map(|field| field.ty.to_token_stream().to_string()).collect::<Vec<_>>().join(", "),
+        Fields::Named(fields) => {
+            let mut fields = fields.named.iter().map(|field| format!("{}: {}", field.ident.to_token_stream(), field.ty.to_token_stream())).collect::<Vec<_>>();
+            fields.sort();
+            format!("{{ {} }}", fields.join(", "))
+        }
+    };
+
+    let cfg = crate::cfg_attributes(&input.attrs);
+    let variants = data.variants.iter().collect::<Vec<_>>();
+    let warnings = variants.iter().enumerate().filter_map(|(index, variant)| {
+        let first = variants[..index].iter().find(|other| shape(&other.fields) == shape(&variant.fields))?;
+        let note = format!("Variant `{}` is never deserialized, as it can not be distinguished from `{}` in the untagged enum `{}`", variant.ident, first.ident, input.ident);
+        let warning = crate::warning(variant.ident.span(), &note);
+
+        Some(quote! { #(#cfg)* #warning })
+    });
+
+    quote! { #(#warnings)* }
+}
+
+/// Enums without data are serialized as the name of their variant, which is declared as a union of string literals, i.e. ```"active" | "inactive"```.
+/// Returns `None` for all other types as well as for enums using a serde representation other than the default one.
+fn string_union(input: &DeriveInput) -> syn::Result<Option<String>> {
+    let names = variant_names(input)?;
+    Ok(names.map(|names| names.iter().map(|name| format!("{name:?}")).collect::<Vec<_>>().join(" | ")))
+}
+
+/// Serialized names of the variants of an enum without data, after applying serde rename rules
+pub(crate) fn variant_names(input: &DeriveInput) -> syn::Result<Option<Vec<String>>> {
+    Ok(unit_variants(input)?.map(|variants| variants.into_iter().map(|(_, name)| name).collect()))
+}
+
+/// Identifiers and serialized names of the variants of an enum without data
+fn unit_variants(input: &DeriveInput) -> syn::Result<Option<Vec<(&Ident, String)>>> {
+    let Data::Enum(data) = &input.data else { return Ok(None) };
+    let is_unit = data.variants.iter().all(|variant| matches!(variant.fields, Fields::Unit));
+    let is_tagged = serde_attr::value(&input.attrs, "tag").is_some() || serde_attr::flag(&input.attrs, "untagged");
+    if data.variants.is_empty() || !is_unit || is_tagged {
+        return Ok(None);
+    }
+
+    let rule = serde_attr::value(&input.attrs, "rename_all");
+    let mut names = vec![];
+    for variant in data.variants.iter().filter(|variant| !is_omitted(&variant.attrs)) {
+        names.push((&variant.ident, variant_name(variant, rule.as_ref())?));
+    }
+
+    Ok(Some(names))
+}
+
+/// Name of a variant after applying ```#[serde(rename = "...")]``` or the rename rule of the enum
+fn variant_name(variant: &Variant, rule: Option<&LitStr>) -> syn::Result<String> {
+    match (serde_attr::value(&variant.attrs, "rename"), rule) {
+        (Some(rename), _) => Ok(rename.value()),
+        (None, Some(rule)) => serde_attr::rename_variant(&variant.ident.to_string(), &rule.value())
+            .ok_or_else(|| syn::Error::new(rule.span(), format!("Unknown rename rule `{}`", rule.value()))),
+        (None, None) => Ok(variant.ident.to_string()),
+    }
+}
+
+/// Name of a field after applying ```#[serde(rename = "...")]``` or the rename rule of the struct
+pub(crate) fn field_name(field: &Field, rule: Option<&LitStr>) -> syn::Result<String> {
+    let ident = field.ident.as_ref().expect("Named fields should have an identifier").to_string();
+    let ident = ident.trim_start_matches("r#");
+
+    match (serde_attr::value(&field.attrs, "rename"), rule) {
+        (Some(rename), _) => Ok(rename.value()),
+        (None, Some(rule)) => serde_attr::rename(ident, &rule.value())
+            .ok_or_else(|| syn::Error::new(rule.span(), format!("Unknown rename rule `{}`", rule.value()))),
+        (None, None) => Ok(ident.to_owned()),
+    }
+}
+
+/// Whether a field or variant is not part of the binding, either because of ```#[bindgen_skip]``` or ```#[serde(skip)]```
+pub(crate) fn is_omitted(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(is_skipped) || ["skip", "skip_serializing"].iter().any(|key| serde_attr::flag(attrs, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_serialized_private_fields_are_reported() {
+        let input: DeriveInput = parse_quote! {
+            struct Account { pub id: u32, secret: String, #[serde(skip)] cache: u32, pub(crate) note: String, #[bindgen_skip] token: String }
+        };
+        let options = EntityOptions::from_args(&syn::parse_str("warn_private").unwrap()).unwrap();
+        let warnings = private_field_warnings(&input, &options).to_string();
+
+        assert!(warnings.contains("Field `secret` of `Account` is private"), "{warnings}");
+        assert_eq!(warnings.matches("is private").count(), 1, "{warnings}");
+    }
+
+    #[test]
+    fn private_fields_are_only_reported_with_warn_private() {
+        let input: DeriveInput = parse_quote! {
+            struct Account { id: u32, secret: String }
+        };
+        let options = EntityOptions::from_args(&Args::default()).unwrap();
+
+        assert!(private_field_warnings(&input, &options).is_empty());
+    }
+}
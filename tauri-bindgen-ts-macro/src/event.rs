@@ -0,0 +1,70 @@
+use quote::{quote, format_ident};
+use syn::DeriveInput;
+
+use crate::args::Args;
+use crate::{config, jsdoc};
+
+/// Creates a test that generates a function for listening to the event with the given struct as payload,
+/// i.e. ```export async function onMyEvent(handler: (payload: MyEvent) => void): Promise<UnlistenFn>```
+pub(crate) fn generate_event_test(args: &Args, input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    args.expect_keys(&["dir", "import_prefix"])?;
+    let Some(event) = args.literal() else {
+        return Err(syn::Error::new_spanned(&input.ident, "Expected the name of the event, i.e. #[tauri_event(\"my-event\")]"));
+    };
+    if let Some(param) = input.generics.params.first() {
+        return Err(syn::Error::new_spanned(param, "Generic structs can not be used as event payload"));
+    }
+
+    let dir = args.dir_key()?;
+    let import_prefix = args.string("import_prefix")?.unwrap_or_else(|| ".".to_owned());
+    let import_prefix = format!("{}/", import_prefix.trim_end_matches('/'));
+    let name = format!("on{}", pascal_case(&event.value()));
+    if name == "on" {
+        return Err(syn::Error::new(event.span(), "The event name needs to contain at least one letter or digit"));
+    }
+
+    let ident = &input.ident;
+    let header = config::header();
+    let import = "import { listen } from \"@tauri-apps/api/event\"\nimport type { UnlistenFn } from \"@tauri-apps/api/event\"";
+    let doc = jsdoc::jsdoc(&jsdoc::docs(&input.attrs));
+    let binding = format!("export async function {name}(handler: (payload: %0) => void): Promise<UnlistenFn> {{ return await listen<%0>('{}', (event) => handler(event.payload)) }}", event.value());
+    let content = format!("{header}\n{import}%1\n\n{doc}{binding}");
+
+    let file_name = format!("{dir}/{name}.ts");
+    let test_fn = format_ident!("export_event_bindings_{}", ident.to_string().to_lowercase());
+    let source = crate::source(ident.span());
+
+    let cfg = crate::cfg_attributes(&input.attrs);
+
+    let setup = config::setup();
+    Ok(quote! {
+        #(#cfg)*
+        #[cfg(test)]
+        #[test]
+        fn #test_fn() {
+            #setup
+            use tauri_bindgen_ts::ts_rs::TS;
+
+            let payload = #ident::name();
+            let import = match tauri_bindgen_ts::ts_rs::Dependency::from_ty::<#ident>() {
+                Some(dependency) => format!("\nimport type {{ {} }} from \"{}{}\"", dependency.ts_name, #import_prefix, dependency.ts_name),
+                None => String::new(),
+            };
+
+            let content = #content.replace("%0", &payload).replace("%1", &import);
+            tauri_bindgen_ts::export::write(#file_name, &tauri_bindgen_ts::export::add_metadata(&content, #source));
+            tauri_bindgen_ts::export::add_command_to_index(#dir, #name);
+        }
+    })
+}
+
+/// Event names are usually kebab-case, such as "my-event", which becomes "MyEvent"
+fn pascal_case(event: &str) -> String {
+    event.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect()
+}
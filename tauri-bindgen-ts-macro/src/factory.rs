@@ -0,0 +1,101 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DataStruct, DeriveInput, Fields};
+
+use crate::types::{TsType, TypeOptions, DURATION};
+use crate::{entity, serde_attr};
+
+/// Creates an expression that evaluates to a factory function for the entity, i.e. ```export function createUser(fields: Partial<User> = {}): User```,
+/// along with the names of the entities whose factories are called by it.
+/// The defaults are the serialized `Default` of the entity if it implements it, and zero values of the field types otherwise.
+pub(crate) fn factory(input: &DeriveInput, options: &TypeOptions) -> syn::Result<TokenStream> {
+    if let Some(param) = input.generics.type_params().next() {
+        return Err(syn::Error::new_spanned(param, "Factories can not be generated for generic entities"));
+    }
+    let Data::Struct(DataStruct { fields: Fields::Named(fields), .. }) = &input.data else {
+        return Err(syn::Error::new_spanned(&input.ident, "Factories can only be generated for structs with named fields"));
+    };
+
+    let rule = serde_attr::value(&input.attrs, "rename_all");
+    let has_default = |attrs: &[Attribute]| serde_attr::flag(attrs, "default") || serde_attr::value(attrs, "default").is_some();
+    let mut entries = vec![];
+    for field in fields.named.iter().filter(|field| !entity::is_omitted(&field.attrs)) {
+        let ident = field.ident.as_ref().expect("Named fields should have an identifier").to_string();
+        let value = default(&TsType::from_type(&field.ty, options));
+        let missing = format!("No default is known for the field `{ident}` of `{}`, implement `Default` for it", input.ident);
+        // Flattened fields are part of the object itself
+        if serde_attr::flag(&field.attrs, "flatten") {
+            entries.push(quote! { format!("...{}", #value.expect(#missing)) });
+            continue;
+        }
+        // Fields that may be missing are declared as optional and can be left out
+        if serde_attr::value(&field.attrs, "skip_serializing_if").is_some() || has_default(&field.attrs) || has_default(&input.attrs) {
+            continue;
+        }
+
+        let key = format!("{:?}", entity::field_name(field, rule.as_ref())?);
+        entries.push(quote! { format!("{}: {}", #key, #value.expect(#missing)) });
+    }
+
+    Ok(quote! {
+        {
+            #[allow(unused_imports)]
+            use tauri_bindgen_ts::export::{DefaultValue, HasFactory, NoDefaultValue, NoFactory};
+            #[allow(unused_mut)]
+            let mut factories: Vec<String> = vec![];
+            let defaults = match (&tauri_bindgen_ts::export::Probe::<Entity>::default()).default_value() {
+                Some(defaults) => defaults,
+                None => {
+                    let entries: Vec<String> = vec![#(#entries),*];
+                    format!("{{ {} }}", entries.join(", "))
+                }
+            };
+            let factory = format!("\n\nexport function create{name}(fields: Partial<{name}> = {{}}): {name} {{ return {{ ...{}, ...fields }} }}", defaults, name = Entity::name());
+            (factory, factories)
+        }
+    })
+}
+
+/// Expands to an expression that evaluates to the TypeScript value of the default of the given type as `Option<String>`.
+/// Nested entities are created by their factory if they have one, and otherwise use their serialized `Default`, which is `None` without it.
+fn default(ty: &TsType) -> TokenStream {
+    let value = |value: &str| quote! { Some(#value.to_owned()) };
+    match ty {
+        TsType::Builtin("void" | "null" | "unknown") | TsType::Nullable(_) => value("null"),
+        TsType::Builtin("string") => value("\"\""),
+        TsType::Builtin("number") => value("0"),
+        TsType::Builtin("bigint") => value("0n"),
+        TsType::Builtin("boolean") => value("false"),
+        TsType::Builtin("Uint8Array") => value("new Uint8Array()"),
+        TsType::Builtin(DURATION) => value("{ secs: 0, nanos: 0 }"),
+        TsType::Array(_) | TsType::ReadonlyArray(_) => value("[]"),
+        TsType::Record(..) => value("{}"),
+        TsType::Entity(ty) => quote! {
+            {
+                let probe = &tauri_bindgen_ts::export::Probe::<#ty>::default();
+                match probe.has_factory() {
+                    true => {
+                        let name = <#ty as tauri_bindgen_ts::ts_rs::TS>::name();
+                        let value = format!("create{name}()");
+                        factories.push(name);
+                        Some(value)
+                    }
+                    false => probe.default_value(),
+                }
+            }
+        },
+        TsType::Generic(ty, _) => quote! { (&tauri_bindgen_ts::export::Probe::<#ty>::default()).default_value() },
+        TsType::FixedArray(inner, len) => {
+            let inner = default(inner);
+            quote! { #inner.map(|value| format!("[{}]", vec![value; #len].join(", "))) }
+        }
+        // The first type of a union is used for its default
+        TsType::Union(elems) => default(&elems[0]),
+        TsType::Tuple(elems) => {
+            let elems = elems.iter().map(default).collect::<Vec<_>>();
+            quote! { [#(#elems),*].into_iter().collect::<Option<Vec<String>>>().map(|elems| format!("[{}]", elems.join(", "))) }
+        }
+        // Branded non-zero numbers have no zero value, custom types are not known to have any
+        TsType::Builtin(_) | TsType::Custom(_) | TsType::Channel(_) => quote! { None::<String> },
+    }
+}
@@ -0,0 +1,112 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Type};
+
+use crate::types::{TsType, TypeOptions, DURATION, NONZERO_BRAND};
+use crate::{entity, serde_attr};
+
+/// Creates an expression that evaluates to a type guard for the entity, i.e. ```export function isUser(value: unknown): value is User```,
+/// and the entity types whose guards are called by it. Nested entities need to be annotated with ```#[entity(guard)]``` as well.
+pub(crate) fn guard(input: &DeriveInput, options: &TypeOptions) -> syn::Result<(TokenStream, Vec<Type>)> {
+    if let Some(param) = input.generics.type_params().next() {
+        return Err(syn::Error::new_spanned(param, "Type guards can not be generated for generic entities"));
+    }
+
+    let mut entities = vec![];
+    let check = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => {
+                let rule = serde_attr::value(&input.attrs, "rename_all");
+                let mut checks = vec![quote! { "typeof value === \"object\" && value !== null && !Array.isArray(value)".to_owned() }];
+                for field in fields.named.iter().filter(|field| !entity::is_omitted(&field.attrs)) {
+                    let ty = TsType::from_type(&field.ty, options);
+                    // Flattened fields are part of the object itself
+                    if serde_attr::flag(&field.attrs, "flatten") {
+                        checks.push(check(&ty, "value", 0, &mut entities));
+                        continue;
+                    }
+
+                    let access = format!("(value as Record<string, unknown>)[{:?}]", entity::field_name(field, rule.as_ref())?);
+                    let field_check = check(&ty, &access, 0, &mut entities);
+                    // Fields that are skipped when serializing may be missing
+                    match serde_attr::value(&field.attrs, "skip_serializing_if") {
+                        Some(_) => checks.push(quote! { format!("({} === undefined || {})", #access, #field_check) }),
+                        None => checks.push(field_check),
+                    }
+                }
+                quote! { [#(#checks),*].join(" && ") }
+            }
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => check(&TsType::from_type(&fields.unnamed[0].ty, options), "value", 0, &mut entities),
+            Fields::Unnamed(fields) => {
+                // Skipped fields are not serialized, so the remaining fields keep their order but not their index
+                let fields = fields.unnamed.iter().filter(|field| !entity::is_omitted(&field.attrs)).collect::<Vec<_>>();
+                let len = fields.len();
+                let elements = fields.iter().enumerate()
+                    .map(|(index, field)| check(&TsType::from_type(&field.ty, options), &format!("(value as unknown[])[{index}]"), 0, &mut entities))
+                    .collect::<Vec<_>>();
+                quote! { format!("Array.isArray(value) && value.length === {} && {}", #len, [#(#elements),*].join(" && ")) }
+            }
+            Fields::Unit => quote! { "value === null".to_owned() },
+        },
+        Data::Enum(_) => match entity::variant_names(input)? {
+            Some(names) => {
+                let check = format!("typeof value === \"string\" && [{}].includes(value)", names.iter().map(|name| format!("{name:?}")).collect::<Vec<_>>().join(", "));
+                quote! { #check.to_owned() }
+            }
+            None => return Err(syn::Error::new_spanned(&input.ident, "Type guards can only be generated for structs and enums without data")),
+        },
+        Data::Union(_) => return Err(syn::Error::new_spanned(&input.ident, "Type guards can not be generated for unions")),
+    };
+
+    let guard = quote! {
+        format!("\n\nexport function is{name}(value: unknown): value is {name} {{ return {} }}", #check, name = Entity::name())
+    };
+
+    Ok((guard, entities))
+}
+
+/// Expands to an expression that evaluates to a TypeScript condition as `String`, which checks whether `value` is of the given type.
+/// Elements of arrays and records are named by their nesting `depth`.
+fn check(ty: &TsType, value: &str, depth: usize, entities: &mut Vec<Type>) -> TokenStream {
+    let element = format!("e{depth}");
+    let condition = |condition: String| quote! { #condition.to_owned() };
+    match ty {
+        TsType::Builtin("void" | "null") => condition(format!("{value} === null")),
+        TsType::Builtin(DURATION) => condition(format!("typeof {value} === \"object\" && {value} !== null && typeof ({value} as Record<string, unknown>)[\"secs\"] === \"number\" && typeof ({value} as Record<string, unknown>)[\"nanos\"] === \"number\"")),
+        // Generic entities do not have a type guard, channels are only passed to commands
+        TsType::Builtin("unknown") | TsType::Custom(_) | TsType::Generic(..) | TsType::Channel(_) => condition("true".to_owned()),
+        TsType::Builtin("Uint8Array") => condition(format!("{value} instanceof Uint8Array")),
+        TsType::Builtin(NONZERO_BRAND) => condition(format!("typeof {value} === \"number\" && {value} !== 0")),
+        TsType::Builtin(name) => condition(format!("typeof {value} === \"{name}\"")),
+        TsType::Entity(ty) => {
+            entities.push(*ty.clone());
+            quote! { format!("is{}({})", <#ty as tauri_bindgen_ts::ts_rs::TS>::name(), #value) }
+        }
+        TsType::Nullable(inner) => {
+            let inner = check(inner, value, depth, entities);
+            quote! { format!("({} === null || {})", #value, #inner) }
+        }
+        TsType::Array(inner) | TsType::ReadonlyArray(inner) => {
+            let inner = check(inner, &element, depth + 1, entities);
+            quote! { format!("Array.isArray({}) && ({} as unknown[]).every(({}: unknown) => {})", #value, #value, #element, #inner) }
+        }
+        TsType::FixedArray(inner, len) => {
+            let inner = check(inner, &element, depth + 1, entities);
+            quote! { format!("Array.isArray({}) && ({} as unknown[]).length === {} && ({} as unknown[]).every(({}: unknown) => {})", #value, #value, #len, #value, #element, #inner) }
+        }
+        TsType::Union(elems) => {
+            let elems = elems.iter().map(|elem| check(elem, value, depth, entities)).collect::<Vec<_>>();
+            quote! { format!("({})", [#(#elems),*].join(" || ")) }
+        }
+        TsType::Tuple(elems) => {
+            let len = elems.len();
+            let elems = elems.iter().enumerate().map(|(index, elem)| check(elem, &format!("({value} as unknown[])[{index}]"), depth, entities)).collect::<Vec<_>>();
+            quote! { format!("Array.isArray({}) && ({} as unknown[]).length === {} && {}", #value, #value, #len, [#(#elems),*].join(" && ")) }
+        }
+        // Keys of JSON objects are always strings, so only the values are checked
+        TsType::Record(_, inner) => {
+            let inner = check(inner, &element, depth + 1, entities);
+            quote! { format!("typeof {} === \"object\" && {} !== null && Object.values({} as object).every(({}: unknown) => {})", #value, #value, #value, #element, #inner) }
+        }
+    }
+}
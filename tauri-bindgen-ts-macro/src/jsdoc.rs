@@ -0,0 +1,32 @@
+use syn::{Attribute, Lit, Meta};
+
+/// Collects the lines of all doc comments, i.e. `/// Some documentation`
+pub(crate) fn docs(attrs: &[Attribute]) -> Vec<String> {
+    attrs.iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(meta)) => match meta.lit {
+                Lit::Str(doc) => Some(doc.value()),
+                _ => None,
+            },
+            _ => None,
+        })
+        // Doc comments keep the space after the slashes
+        .map(|line| line.strip_prefix(' ').map(str::to_owned).unwrap_or(line))
+        .collect()
+}
+
+/// Formats the given lines as JSDoc block, including a trailing newline. Returns an empty string if there are no lines.
+pub(crate) fn jsdoc(lines: &[String]) -> String {
+    if lines.is_empty() {
+        return String::new();
+    }
+
+    let lines = lines.iter()
+        .map(|line| line.replace("*/", "*\\/"))
+        .map(|line| if line.is_empty() { " *".to_owned() } else { format!(" * {line}") })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("/**\n{lines}\n */\n")
+}
@@ -0,0 +1,137 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Fields};
+
+use crate::types::{TsType, TypeOptions, DURATION, NONZERO_BRAND};
+use crate::{entity, jsdoc, serde_attr};
+
+/// Creates an expression that evaluates to the JSON Schema of the entity as `String`, which is completed with ```$schema``` and ```title``` when it is written.
+/// Nested entities are referenced by their own schema file, i.e. ```{ "$ref": "./Address.schema.json" }```, so these need to be annotated with ```#[entity(json_schema)]``` as well.
+pub(crate) fn schema(input: &DeriveInput, options: &TypeOptions) -> syn::Result<TokenStream> {
+    if let Some(param) = input.generics.type_params().next() {
+        return Err(syn::Error::new_spanned(param, "JSON Schemas can not be generated for generic entities"));
+    }
+
+    let schema = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => object(&data.fields, &input.attrs, options)?,
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => json_schema(&TsType::from_type(&fields.unnamed[0].ty, options)),
+            Fields::Unnamed(fields) => {
+                let elements = fields.unnamed.iter().filter(|field| !entity::is_omitted(&field.attrs)).map(|field| json_schema(&TsType::from_type(&field.ty, options))).collect::<Vec<_>>();
+                tuple(&elements)
+            }
+            Fields::Unit => quote! { r#"{ "type": "null" }"#.to_owned() },
+        },
+        Data::Enum(_) => match entity::variant_names(input)? {
+            Some(names) => {
+                let schema = format!(r#"{{ "type": "string", "enum": {} }}"#, json(names));
+                quote! { #schema.to_owned() }
+            }
+            None => return Err(syn::Error::new_spanned(&input.ident, "JSON Schemas can only be generated for structs and enums without data")),
+        },
+        Data::Union(_) => return Err(syn::Error::new_spanned(&input.ident, "JSON Schemas can not be generated for unions")),
+    };
+
+    Ok(describe(schema, &input.attrs))
+}
+
+/// Schema of a struct with named fields, using the same field names as serde
+fn object(fields: &Fields, attrs: &[Attribute], options: &TypeOptions) -> syn::Result<TokenStream> {
+    let rule = serde_attr::value(attrs, "rename_all");
+    let has_default = |attrs: &[Attribute]| serde_attr::flag(attrs, "default") || serde_attr::value(attrs, "default").is_some();
+    let mut properties = vec![];
+    let mut required = vec![];
+    let mut flattened = vec![];
+    for field in fields.iter().filter(|field| !entity::is_omitted(&field.attrs)) {
+        // Flattened fields are part of the object itself
+        if serde_attr::flag(&field.attrs, "flatten") {
+            flattened.push(json_schema(&TsType::from_type(&field.ty, options)));
+            continue;
+        }
+
+        let name = entity::field_name(field, rule.as_ref())?;
+        let schema = describe(json_schema(&TsType::from_type(&field.ty, options)), &field.attrs);
+        // Fields that are skipped when serializing may be missing, as well as fields that have a default value
+        if serde_attr::value(&field.attrs, "skip_serializing_if").is_none() && !has_default(&field.attrs) && !has_default(attrs) {
+            required.push(name.clone());
+        }
+        let name = json(name);
+        properties.push(quote! { format!("{}: {}", #name, #schema) });
+    }
+
+    let required = json(required);
+    let additional = match serde_attr::flag(attrs, "deny_unknown_fields") && flattened.is_empty() {
+        true => r#", "additionalProperties": false"#,
+        false => "",
+    };
+    let object = quote! { format!(r#"{{ "type": "object", "properties": {{ {} }}, "required": {}{} }}"#, [#(#properties),*].join(", "), #required, #additional) };
+
+    Ok(match flattened.is_empty() {
+        true => object,
+        false => quote! { format!(r#"{{ "allOf": [{}] }}"#, [#object, #(#flattened),*].join(", ")) },
+    })
+}
+
+/// Doc comments are added as description of the schema
+fn describe(schema: TokenStream, attrs: &[Attribute]) -> TokenStream {
+    let docs = jsdoc::docs(attrs);
+    if docs.is_empty() {
+        return schema;
+    }
+
+    let description = docs.join("\n");
+    quote! { tauri_bindgen_ts::export::describe_json_schema(&#schema, #description) }
+}
+
+/// Serializes a value that is known when expanding the macro
+fn json(value: impl Into<serde_json::Value>) -> String {
+    value.into().to_string()
+}
+
+fn tuple(elements: &[TokenStream]) -> TokenStream {
+    let len = elements.len();
+    quote! { format!(r#"{{ "type": "array", "prefixItems": [{}], "minItems": {}, "maxItems": {} }}"#, [#(#elements),*].join(", "), #len, #len) }
+}
+
+/// Expands to an expression that evaluates to the JSON Schema of the given type as `String`
+fn json_schema(ty: &TsType) -> TokenStream {
+    match ty {
+        TsType::Builtin("void" | "null") => quote! { r#"{ "type": "null" }"#.to_owned() },
+        TsType::Builtin("string") => quote! { r#"{ "type": "string" }"#.to_owned() },
+        TsType::Builtin("number") => quote! { r#"{ "type": "number" }"#.to_owned() },
+        TsType::Builtin("boolean") => quote! { r#"{ "type": "boolean" }"#.to_owned() },
+        // Large integers are serialized as JSON number as well
+        TsType::Builtin("bigint") => quote! { r#"{ "type": "integer" }"#.to_owned() },
+        TsType::Builtin(DURATION) => {
+            quote! { r#"{ "type": "object", "properties": { "secs": { "type": "integer" }, "nanos": { "type": "integer" } }, "required": ["secs", "nanos"] }"#.to_owned() }
+        }
+        TsType::Builtin(NONZERO_BRAND) => quote! { r#"{ "type": "integer", "not": { "const": 0 } }"#.to_owned() },
+        // Binary data is serialized as array of bytes, regardless of how it is declared in TypeScript
+        TsType::Builtin("Uint8Array") => quote! { r#"{ "type": "array", "items": { "type": "integer", "minimum": 0, "maximum": 255 } }"#.to_owned() },
+        // Any other value is accepted, such as `serde_json::Value`. Generic entities do not have a schema, channels are only passed to commands.
+        TsType::Builtin(_) | TsType::Custom(_) | TsType::Generic(..) | TsType::Channel(_) => quote! { "{}".to_owned() },
+        TsType::Entity(ty) => quote! { format!(r#"{{ "$ref": "./{}.schema.json" }}"#, <#ty as tauri_bindgen_ts::ts_rs::TS>::name()) },
+        TsType::Nullable(inner) => {
+            let inner = json_schema(inner);
+            quote! { format!(r#"{{ "anyOf": [{}, {{ "type": "null" }}] }}"#, #inner) }
+        }
+        TsType::Array(inner) | TsType::ReadonlyArray(inner) => {
+            let inner = json_schema(inner);
+            quote! { format!(r#"{{ "type": "array", "items": {} }}"#, #inner) }
+        }
+        TsType::FixedArray(inner, len) => {
+            let inner = json_schema(inner);
+            quote! { format!(r#"{{ "type": "array", "items": {}, "minItems": {}, "maxItems": {} }}"#, #inner, #len, #len) }
+        }
+        TsType::Tuple(elems) => tuple(&elems.iter().map(json_schema).collect::<Vec<_>>()),
+        TsType::Union(elems) => {
+            let elems = elems.iter().map(json_schema).collect::<Vec<_>>();
+            quote! { format!(r#"{{ "anyOf": [{}] }}"#, [#(#elems),*].join(", ")) }
+        }
+        // JSON object keys are always strings, even if the map is keyed by numbers
+        TsType::Record(_, value) => {
+            let value = json_schema(value);
+            quote! { format!(r#"{{ "type": "object", "additionalProperties": {} }}"#, #value) }
+        }
+    }
+}
@@ -1,117 +1,1093 @@
 use proc_macro::TokenStream;
-use quote::{quote, format_ident};
-use syn::{ItemFn, FnArg, Type, Pat, PatType, Path, Ident};
+use quote::{quote, quote_spanned, format_ident, ToTokens};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, parse_quote, Attribute, DeriveInput, ItemFn, ItemImpl, ItemMod, ItemType, ImplItem, FnArg, Type, Pat, PatType, Ident, LitStr, ReturnType, Signature, Visibility};
+use syn::visit_mut::{self, VisitMut};
+
+mod args;
+mod command_module;
+mod config;
+mod descriptor;
+mod entity;
+mod event;
+mod factory;
+mod guard;
+mod jsdoc;
+mod json_schema;
+mod serde_attr;
+mod types;
+mod zod;
+use args::Args;
+use entity::EntityOptions;
+use types::{TsType, TypeOptions};
 
 /// Creates a test that generates a corresponding TypeScript interface for this struct. To generate TypeScript bindings, run ```cargo test```
 /// **Important:** In order for this macro to work, both ts_rs and serde need to be in scope. This can be achieved by importing the prelude: ```use tauri_bindgen_ts::prelude::*```
 ///
 /// By default, the location is set to "../src-gen" which results in a top-level directory "src-gen in your Tauri app.
 /// A different output directory can be specified by passing a path as string argument, i.e. ```#[entity("./my-custom-dir)"] struct MyStruct { }```
+/// See the README for all options and how Rust types are declared in TypeScript.
 #[proc_macro_attribute]
 pub fn entity(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let item: proc_macro2::TokenStream = item.into();
-    let dir = format!("{}/", parse_dir_arg(&attr));
+    let args = parse_macro_input!(attr as Args);
+    let options = match EntityOptions::from_args(&args) {
+        Ok(options) => options,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    if let Ok(alias) = syn::parse::<ItemType>(item.clone()) {
+        descriptor::write_entity(&alias.ident, &options.rename.clone().unwrap_or_else(|| alias.ident.to_string()), None);
+        return match args.expect_keys(&["module", "rename", "ts_nocheck"]).and_then(|_| entity::generate_alias(&alias, &options)) {
+            Ok(test) => quote! {
+                #alias
+                #test
+            }.into(),
+            Err(err) => err.to_compile_error().into(),
+        };
+    }
+
+    let mut input = parse_macro_input!(item as DeriveInput);
+    let name = options.rename.clone().or_else(|| serde_attr::value(&input.attrs, "rename").map(|rename| rename.value())).unwrap_or_else(|| input.ident.to_string());
+    descriptor::write_entity(&input.ident, &name, options.import_from.as_deref());
+    let export_to = format!("{}/", options.out_dir());
+    let rename = options.rename.as_ref().map(|name| quote! { #[ts(rename = #name)] });
+    if let Err(err) = entity::validate(&input) {
+        return err.to_compile_error().into();
+    }
+    if options.optional_fields {
+        entity::make_optional(&mut input);
+    }
+    if let Err(err) = entity::explicit_types(&mut input, &options.type_options()) {
+        return err.to_compile_error().into();
+    }
+    entity::skip_phantom_data(&mut input);
+    entity::split_serde_attributes(&mut input);
+    if let Err(err) = entity::rename_fields(&mut input) {
+        return err.to_compile_error().into();
+    }
+    let test = match entity::generate_entity_test(&input, &options) {
+        Ok(test) => test,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let warnings = entity::ambiguity_warnings(&input);
+    let large_int_warnings = entity::large_int_warnings(&input, &options);
+    let private_field_warnings = entity::private_field_warnings(&input, &options);
+    let derives = entity::derives(&input, &options);
+    entity::replace_attributes(&mut input);
 
     quote! {
-        #[derive(ts_rs::TS, serde::Serialize, serde::Deserialize)]
-        #[ts(export)]
-        #[ts(export_to=#dir)]
-        #item
+        #[derive(ts_rs::TS, serde::Serialize, serde::Deserialize #(, #derives)*)]
+        #[ts(export_to=#export_to)]
+        #rename
+        #input
+        #test
+        #warnings
+        #large_int_warnings
+        #private_field_warnings
     }.into()
 }
 
+/// Creates a test that generates a TypeScript function for listening to the event with the given name. To generate TypeScript bindings, run ```cargo test```
+/// The annotated struct is the payload of the event and needs to be an entity as well, i.e.
+/// ```ignore
+/// #[entity]
+/// #[tauri_event("download-progress")]
+/// struct DownloadProgress { percent: u8 }
+/// ```
+/// generates ```function onDownloadProgress(handler: (payload: DownloadProgress) => void): Promise<UnlistenFn>```.
+/// Doc comments on the struct are added to the generated function as JSDoc.
+///
+/// By default, the location is set to "../src-gen". As the string argument is the name of the event, a different output directory can be specified with ```dir```,
+/// i.e. ```#[tauri_event("my-event", dir = "./my-custom-dir")]```. Same as for commands, the import prefix of the payload type can be changed with ```import_prefix```.
+#[proc_macro_attribute]
+pub fn tauri_event(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as Args);
+    let input = parse_macro_input!(item as DeriveInput);
+
+    match event::generate_event_test(&args, &input) {
+        Ok(test) => quote! {
+            #input
+            #test
+        }.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
 /// Turns this function into a Tauri command and creates a test that generates a TypeScript binding to this function. To generate TypeScript bindings, run ```cargo test```
 /// **Important:** In order for this macro to work, both ts_rs and serde need to be in scope. This can be achieved by importing the prelude: ```use tauri_bindgen_ts::prelude::*```
 ///
 /// By default, the location is set to "../src-gen" which results in a top-level directory "src-gen in your Tauri app.
-/// A different output directory can be specified by passing a path as string argument, i.e. ```#[entity("./my-custom-dir)"] struct MyStruct { }```
+/// A different output directory can be specified by passing a path as string argument, i.e. ```#[command("./my-custom-dir")] fn greet() { }```
+/// See the README for all options, the arguments injected by Tauri and how return types are declared.
 #[proc_macro_attribute]
 pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let func = syn::parse::<ItemFn>(item.clone()).expect("This attribute should be used on a function!");
-    let item: proc_macro2::TokenStream = item.into();
+    let args = parse_macro_input!(attr as Args);
+    match CommandOptions::from_args(&args) {
+        Ok(options) => expand_command(&options, item),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Same as ```#[command]```, but for a command of the Tauri plugin with the given name, i.e. ```#[plugin_command("my-plugin")]```.
+/// The binding invokes ```'plugin:my-plugin|function_name'```, the command itself still needs to be registered with the invoke handler of the plugin.
+/// As the plain string argument is the name of the plugin, the export directory is specified with ```dir```, i.e. ```#[plugin_command("my-plugin", dir = "../src-gen")]```.
+/// Accepts the same arguments as ```#[command]``` otherwise.
+#[proc_macro_attribute]
+pub fn plugin_command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as Args);
+    match CommandOptions::from_plugin_args(&args) {
+        Ok(options) => expand_command(&options, item),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand_command(options: &CommandOptions, item: TokenStream) -> TokenStream {
+    let mut item = syn::parse::<ItemFn>(item).expect("This attribute should be used on a function!");
+    if let Some(receiver) = item.sig.inputs.iter().find(|arg| is_receiver(arg)) {
+        return syn::Error::new_spanned(receiver, RECEIVER_ERROR).to_compile_error().into();
+    }
+
+    let func = match func_metadata(item.sig.clone(), &item.attrs, options) {
+        Ok(func) => func,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let command = tauri_command(options);
+    item.attrs.retain(|attr| !serde_attr::is_serde(attr));
+    // Tauri registers commands by the name of their function, so a renamed command is a wrapper with that name
+    let wrapper = match func.ident != func.invoke_name {
+        true => match command_wrapper(&item.sig.ident.to_token_stream(), &item.vis, &item.attrs, item.sig.clone(), command, &func) {
+            Ok(wrapper) => Some(wrapper),
+            Err(err) => return err.to_compile_error().into(),
+        },
+        false => {
+            place_command(&mut item, command, options.command_last);
+            None
+        }
+    };
 
-    let dir = parse_dir_arg(&attr);
-    let func = func_metadata(func);
-    let test = generate_test(func, dir);
+    let test = generate_test(func, options);
 
     quote! {
-        #[tauri::command]
         #item
+        #wrapper
         #test
     }.into()
 }
 
+/// Adds the Tauri attribute to the function, which expands before the attributes below ```#[command]``` unless it is placed after them,
+/// so that other attribute macros can change the function before Tauri declares the command
+fn place_command(item: &mut ItemFn, command: Attribute, last: bool) {
+    match last {
+        true => item.attrs.push(command),
+        false => item.attrs.insert(0, command),
+    }
+}
+
+/// Turns every public method in this impl block into a Tauri command and creates a test that generates a TypeScript binding for each of them,
+/// as if each method was annotated with ```#[command]```. Accepts the same arguments as ```#[command]```.
+/// Arguments that are injected by Tauri, such as ```state: State<Self>```, are omitted from the generated bindings.
+/// Methods annotated with ```#[command]``` or ```#[plugin_command]``` are commands as well, even if they are not public, and use their own arguments instead of those of the group.
+///
+/// As the attribute of Tauri can not be used in impl blocks, each command is a free function next to the impl block with the name of the method,
+/// which calls the method and is registered with ```tauri::generate_handler![counter_value]``` rather than ```Counter::counter_value```.
+/// Methods need to be associated functions, state is passed as an injected argument such as ```state: State<'_, Self>```.
+#[proc_macro_attribute]
+pub fn command_group(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as Args);
+    let options = match CommandOptions::from_args(&args) {
+        Ok(options) => options,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    if let Some(rename) = ["rename", "invoke_name", "ts_name"].into_iter().find_map(|key| args.span(key)) {
+        return syn::Error::new(rename, "Only single commands can be renamed").to_compile_error().into();
+    }
+    if let Some(return_type) = args.span("return_type") {
+        return syn::Error::new(return_type, "The return type can only be specified for single commands").to_compile_error().into();
+    }
+    if let Some(position) = args.span("position") {
+        return syn::Error::new(position, "The position of the Tauri attribute can only be specified for single commands, as the commands of a group have no other attributes").to_compile_error().into();
+    }
+
+    let mut group = parse_macro_input!(item as ItemImpl);
+    let mut commands = vec![];
+    let mut tests = vec![];
+    for item in group.items.iter_mut() {
+        let ImplItem::Method(method) = item else { continue };
+        // Methods annotated with `#[command]` are generated by the group instead, using their own options
+        let options = match command_module::method_options(method, &options) {
+            Ok(Some(options)) => options,
+            Ok(None) => continue,
+            Err(err) => return err.to_compile_error().into(),
+        };
+        method.attrs.retain(|attr| !command_module::is_command(attr));
+        if let Some(receiver) = method.sig.inputs.iter().find(|arg| is_receiver(arg)) {
+            return syn::Error::new_spanned(receiver, RECEIVER_ERROR).to_compile_error().into();
+        }
+
+        // Commands and their bindings are generated as free functions, where `Self` cannot be used
+        let mut sig = method.sig.clone();
+        ReplaceSelf(&group.self_ty).visit_signature_mut(&mut sig);
+        let result = func_metadata(sig.clone(), &method.attrs, &options).and_then(|func| {
+            let (self_ty, ident) = (&group.self_ty, &method.sig.ident);
+            let command = command_wrapper(&quote! { <#self_ty>::#ident }, &method.vis, &method.attrs, sig, tauri_command(&options), &func)?;
+            Ok((command, generate_test(func, &options)))
+        });
+        match result {
+            Ok((command, test)) => {
+                commands.push(command);
+                tests.push(test);
+            }
+            Err(err) => return err.to_compile_error().into(),
+        }
+        method.attrs.retain(|attr| !serde_attr::is_serde(attr));
+    }
 
-/// Parse the specified export dir from attributes. Defaults to "../src-gen"
-fn parse_dir_arg(attr: &TokenStream) -> String {
-    // TODO: Validate path
-    let dir = attr.to_string();
-    let dir = dir.trim_matches(|c| c == '"' || c == '\'' );
-    if dir.is_empty() { "../src-gen".to_owned() } else { dir.to_owned() }
+    quote! {
+        #group
+        #(#commands)*
+        #(#tests)*
+    }.into()
+}
+
+/// Tauri can only call commands without an instance
+const RECEIVER_ERROR: &str = "Methods with `self` can not be commands, as Tauri calls commands without an instance. Use an associated function in a #[command_group] instead, i.e. with `state: tauri::State<'_, Self>`";
+
+/// Free function with the name of the command and the same arguments as the called function, which is the actual Tauri command.
+/// The attribute of Tauri can not be used in impl blocks, as it declares a macro next to the function for ```generate_handler!```,
+/// and renamed commands need a function with their name, as Tauri registers commands by the name of their function.
+fn command_wrapper(callee: &proc_macro2::TokenStream, vis: &Visibility, attrs: &[Attribute], mut sig: Signature, command: Attribute, func: &Func) -> syn::Result<proc_macro2::TokenStream> {
+    let mut args = vec![];
+    for arg in sig.inputs.iter_mut() {
+        let FnArg::Typed(PatType { pat, .. }) = arg else { continue };
+        // Tauri takes the keys of the arguments from their names, which therefore need to stay the same
+        let Pat::Ident(pat) = &mut **pat else {
+            return Err(syn::Error::new_spanned(pat, "Arguments of commands in a #[command_group] or of renamed commands need to be plain names, as Tauri passes them by name"));
+        };
+        pat.mutability = None;
+        args.push(pat.ident.clone());
+    }
+
+    let call = match sig.asyncness {
+        Some(_) => quote! { #callee(#(#args),*).await },
+        None => quote! { #callee(#(#args),*) },
+    };
+    // The name was validated to be an identifier, but it does not need to be snake_case
+    let renamed = (func.ident != func.invoke_name).then(|| quote! { #[allow(non_snake_case)] });
+    sig.ident = Ident::new(&func.invoke_name, func.ident.span());
+    let cfg = cfg_attributes(attrs);
+    let docs = attrs.iter().filter(|attr| attr.path.is_ident("doc"));
+
+    Ok(quote! {
+        #(#cfg)*
+        #(#docs)*
+        #command
+        #renamed
+        #vis #sig {
+            #call
+        }
+    })
+}
+
+/// Generates a file that exports all commands defined in this module as a single object, so that the frontend only needs one import.
+/// Functions annotated with ```#[command]``` or ```#[plugin_command]``` and impl blocks annotated with ```#[command_group]``` are collected, the individual bindings are still generated as well.
+/// Doc comments on the module are added to the generated object as JSDoc.
+///
+/// ```ignore
+/// #[command_module]
+/// mod api {
+///     #[command]
+///     fn get_user() -> User { }
+/// }
+/// ```
+/// creates a file "api.ts" containing ```export const api = { get_user }```, which can be used as ```api.get_user()```.
+/// The name of the object can be changed with ```name```, i.e. ```#[command_module(name = "client")]```. Same as for commands, the export directory can be passed as string argument.
+#[proc_macro_attribute]
+pub fn command_module(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as Args);
+    let module = parse_macro_input!(item as ItemMod);
+
+    match command_module::generate_module_test(&args, &module) {
+        Ok(test) => quote! {
+            #module
+            #test
+        }.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// The Tauri command attribute, which needs to know the rule for the keys of arguments if one is specified.
+/// Tauri has no option to rename a command, which is instead registered by the name of its function.
+fn tauri_command(options: &CommandOptions) -> Attribute {
+    match &options.rename_all {
+        Some(rule) => parse_quote! { #[tauri::command(rename_all = #rule)] },
+        None => parse_quote! { #[tauri::command] },
+    }
+}
+
+/// Whether the argument is the receiver of a method, i.e. ```&self``` or ```self: Box<Self>```
+fn is_receiver(arg: &FnArg) -> bool {
+    match arg {
+        FnArg::Receiver(_) => true,
+        FnArg::Typed(arg) => matches!(&*arg.pat, Pat::Ident(pat) if pat.ident == "self"),
+    }
+}
+
+/// Replaces all occurrences of `Self` with the given type
+struct ReplaceSelf<'a>(&'a Type);
+
+impl VisitMut for ReplaceSelf<'_> {
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        match ty {
+            Type::Path(path) if path.qself.is_none() && path.path.is_ident("Self") => *ty = self.0.clone(),
+            _ => visit_mut::visit_type_mut(self, ty),
+        }
+    }
+}
+
+/// Options that can be passed to the command attribute
+#[derive(Clone)]
+struct CommandOptions {
+    /// Directory to which the resulting file will be exported
+    dir: String,
+    /// Path prefix from which entity types are imported, always ends with a slash
+    import_prefix: String,
+    /// Name of the command, if it differs from the function name
+    rename: Option<String>,
+    /// Key with which the command is invoked, if it differs from the name of the TypeScript function
+    invoke_name: Option<String>,
+    /// Name of the TypeScript function, if it differs from the key with which the command is invoked
+    ts_name: Option<String>,
+    /// TypeScript type of the error with which the command rejects, for which a type guard is generated
+    error_type: Option<String>,
+    /// Permissions of the Tauri v2 ACL that are required to invoke the command, i.e. ```acl = "fs:allow-read"```
+    acl: Vec<String>,
+    /// Rule with which Tauri renames the keys of arguments, which defaults to camelCase
+    rename_all: Option<String>,
+    /// Types with which the command resolves instead of the declared return type, i.e. the concrete type of `impl Serialize`.
+    /// Several types separated by `|` are declared as union, i.e. the documented shapes of a `serde_json::Value`.
+    return_type: Option<Vec<Type>>,
+    /// Place the Tauri attribute after the other attributes of the function instead of before them, with ```position = "after"```
+    command_last: bool,
+    /// Milliseconds after which the binding rejects if the command did not resolve yet
+    timeout_ms: Option<u64>,
+    /// Milliseconds for which the debounced variant of the binding waits for further calls before invoking the command
+    debounce_ms: Option<u64>,
+    /// Whether the binding yields the messages sent through the `tauri::ipc::Channel<T>` argument instead of taking a channel
+    streaming: bool,
+    /// Subdirectory of `dir` to which the file is exported instead
+    module: Option<String>,
+    types: TypeOptions,
+    /// Import statement of `invoke`, as configured by the "tauri-v2" feature or the config
+    invoke_import: String,
+    /// Whether to generate a mock of the binding for frontend tests
+    mock: bool,
+    /// Whether to add the command to the overview of all bindings, as configured with ```generate_readme = true```
+    readme: bool,
+    /// Name of the Tauri plugin that provides the command, if it is a plugin command
+    plugin: Option<String>,
+}
+
+impl CommandOptions {
+    fn from_args(args: &Args) -> syn::Result<Self> {
+        args.expect_keys(&["acl", "bytes_as", "debounce_ms", "error_type", "import_prefix", "invoke_name", "json_value", "large_int", "mock", "module", "position", "readonly_sets", "rename", "rename_all", "return_type", "streaming", "timeout_ms", "ts_name"])?;
+        Self::parse(args, args.dir()?, None)
+    }
+
+    /// Options of ```#[plugin_command("my-plugin")]```, where the plain string argument is the name of the plugin instead of the export directory
+    fn from_plugin_args(args: &Args) -> syn::Result<Self> {
+        args.expect_keys(&["acl", "bytes_as", "debounce_ms", "dir", "error_type", "import_prefix", "invoke_name", "json_value", "large_int", "mock", "module", "position", "readonly_sets", "rename", "rename_all", "return_type", "streaming", "timeout_ms", "ts_name"])?;
+        let Some(plugin) = args.literal() else {
+            return Err(syn::Error::new(proc_macro2::Span::call_site(), "Expected the name of the plugin, i.e. #[plugin_command(\"my-plugin\")]"));
+        };
+        if !is_invoke_key(&plugin.value()) || plugin.value().contains('|') {
+            return Err(syn::Error::new(plugin.span(), format!("`{}` is not a valid name for a Tauri plugin", plugin.value())));
+        }
+
+        Self::parse(args, args.dir_key()?, Some(plugin.value()))
+    }
+
+    fn parse(args: &Args, dir: String, plugin: Option<String>) -> syn::Result<Self> {
+        let import_prefix = args.string("import_prefix")?.unwrap_or_else(|| ".".to_owned());
+        let import_prefix = format!("{}/", import_prefix.trim_end_matches('/'));
+
+        let bytes_as_uint8_array = match args.string("bytes_as")?.as_deref() {
+            None | Some("number[]") => false,
+            Some("Uint8Array") => true,
+            Some(other) => return Err(syn::Error::new(args.span("bytes_as").expect("Argument should be present"), format!("Unknown type `{other}` for binary data, expected \"number[]\" or \"Uint8Array\""))),
+        };
+        let types = TypeOptions {
+            readonly_sets: args.flag("readonly_sets")?,
+            json_value: args.string("json_value")?,
+            bytes_as_uint8_array,
+            large_int: types::large_int(args)?,
+            ..TypeOptions::default()
+        };
+
+        let error_type = args.string("error_type")?;
+        if let Some(error_type) = error_type.as_deref().filter(|error_type| !is_identifier(error_type)) {
+            return Err(syn::Error::new(args.span("error_type").expect("Argument should be present"), format!("`{error_type}` is not a valid name for a TypeScript type")));
+        }
+
+        let acl = args.strings("acl")?;
+        if let Some(permission) = acl.iter().find(|permission| !is_permission(&permission.value())) {
+            return Err(syn::Error::new(permission.span(), format!("`{}` is not a valid permission, i.e. \"fs:allow-read\"", permission.value())));
+        }
+        let acl = acl.iter().map(LitStr::value).collect();
+
+        let rename_all = args.string("rename_all")?;
+        if let Some(rule) = rename_all.as_deref().filter(|rule| serde_attr::rename("argument_name", rule).is_none()) {
+            return Err(syn::Error::new(args.span("rename_all").expect("Argument should be present"), format!("Unknown rename rule `{rule}`")));
+        }
+
+        let return_type = match args.string("return_type")? {
+            Some(types) => Some(types.split('|').map(str::trim).map(|ty| {
+                syn::parse_str::<Type>(ty).map_err(|_| syn::Error::new(args.span("return_type").expect("Argument should be present"), format!("`{ty}` is not a valid type")))
+            }).collect::<syn::Result<Vec<_>>>()?),
+            None => None,
+        };
+
+        let timeout_ms = args.integer("timeout_ms")?;
+        if timeout_ms == Some(0) {
+            return Err(syn::Error::new(args.span("timeout_ms").expect("Argument should be present"), "The timeout needs to be at least 1 ms"));
+        }
+        let debounce_ms = args.integer("debounce_ms")?;
+        if debounce_ms == Some(0) {
+            return Err(syn::Error::new(args.span("debounce_ms").expect("Argument should be present"), "The debounce delay needs to be at least 1 ms"));
+        }
+        let streaming = args.flag("streaming")?;
+        if let Some(key) = ["timeout_ms", "debounce_ms"].into_iter().find(|key| streaming && args.span(key).is_some()) {
+            return Err(syn::Error::new(args.span(key).expect("Argument should be present"), format!("`{key}` can not be combined with `streaming`")));
+        }
+
+        let command_last = match args.string("position")?.as_deref() {
+            None | Some("before") => false,
+            Some("after") => true,
+            Some(position) => return Err(syn::Error::new(args.span("position").expect("Argument should be present"), format!("Unknown position `{position}`, expected \"before\" or \"after\""))),
+        };
+
+        let config = config::config()?;
+        // Commands can opt out of the project-wide default with `mock = false`
+        let mock = args.optional_flag("mock")?.unwrap_or(config.generate_mocks);
+
+        Ok(CommandOptions {
+            dir,
+            import_prefix,
+            rename: args.string("rename")?,
+            invoke_name: args.string("invoke_name")?,
+            ts_name: args.string("ts_name")?,
+            error_type,
+            acl,
+            rename_all,
+            return_type,
+            timeout_ms,
+            debounce_ms,
+            streaming,
+            command_last,
+            module: args.module()?,
+            types,
+            invoke_import: config.invoke_import(),
+            mock,
+            readme: config.generate_readme,
+            plugin,
+        })
+    }
+
+    /// Key with which the frontend invokes the command, which is prefixed with the plugin for plugin commands
+    fn invoke_key(&self, invoke_name: &str) -> String {
+        match &self.plugin {
+            Some(plugin) => format!("plugin:{plugin}|{invoke_name}"),
+            None => invoke_name.to_owned(),
+        }
+    }
+
+    /// Arguments as object with the keys that Tauri expects, i.e. ```{ userId: user_id }```. The keys are camelCase, unless another rule is specified with ```rename_all```.
+    fn payload(&self, args: &[(Ident, TsType)]) -> String {
+        let rule = self.rename_all.as_deref().unwrap_or("camelCase");
+        args.iter()
+            .map(|(ident, ty)| {
+                let name = ident.to_string();
+                let value = match ty {
+                    TsType::Builtin("Uint8Array") => format!("Array.from({name})"),
+                    ty if is_binary(ty) => format!("{name} && Array.from({name})"),
+                    _ => name.clone(),
+                };
+                let key = serde_attr::rename(&name, rule).unwrap_or_else(|| name.clone());
+                match () {
+                    _ if key == value => value,
+                    _ if is_identifier(&key) => format!("{key}: {value}"),
+                    _ => format!("{key:?}: {value}"),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Directory to which the file is actually exported, which is the subdirectory for the module if one is specified
+    fn out_dir(&self) -> String {
+        match &self.module {
+            Some(module) => format!("{}/{module}", self.dir),
+            None => self.dir.clone(),
+        }
+    }
 }
 
 struct Func {
+    ident: Ident,
+    /// Name of the TypeScript function, which is also the name of the generated file
     name: String,
-    args: Vec<(Ident, Path)>,
+    /// Key with which the command is registered in Tauri and invoked from the frontend
+    invoke_name: String,
+    docs: Vec<String>,
+    args: Vec<(Ident, TsType)>,
+    ret: TsType,
+    /// Type with which the command rejects, if it returns a `Result`
+    err: Option<TsType>,
+    /// An `impl Trait` in the return type, which is declared as `unknown` since no ```return_type``` is specified
+    unresolved: Option<Type>,
+    /// Types in the signature that contain 128-bit integers, which are declared as `string` with a warning unless ```large_int``` is passed
+    large_ints: Vec<Type>,
+    /// ```#[cfg(...)]``` attributes of the command, so that its binding is only generated if the command is compiled
+    cfg: Vec<Attribute>,
+    /// Channel argument of a streaming command and the type of its messages, which the binding creates instead of taking it as argument
+    stream: Option<(Ident, TsType)>,
 }
 
-fn func_metadata(func: ItemFn) -> Func {
-    let name = func.sig.ident.to_string();
-    // TODO: Implement mechanism to skip args such as tauris app handle (can be done with attrs)
-    let args = func.sig.inputs.into_iter()
-        .filter_map(|arg| if let FnArg::Typed(t) = arg { Some(t) } else { panic!("Only top-level functions are allowed as commands!") })
-        .collect::<Vec<_>>();
-    // TODO: Support more function arg types
-    let args = types(&args);
+impl Func {
+    /// Arguments passed when invoking the command, which includes the channel of a streaming command
+    fn invoked_args(&self) -> Vec<(Ident, TsType)> {
+        self.args.iter().cloned().chain(self.stream.iter().map(|(ident, ty)| (ident.clone(), TsType::Channel(Box::new(ty.clone()))))).collect()
+    }
 
-    Func { name, args }
+    /// Whether the binding imports `Channel` from the Tauri API
+    fn uses_channel(&self) -> bool {
+        self.stream.is_some() || self.args.iter().any(|(_, ty)| matches!(ty, TsType::Channel(_)))
+    }
 }
 
-fn types(args: &[PatType]) -> Vec<(Ident, Path)> {
+fn func_metadata(sig: Signature, attrs: &[Attribute], options: &CommandOptions) -> syn::Result<Func> {
+    let ident = sig.ident.clone();
+    let (invoke_name, name) = func_names(&ident, attrs, options)?;
+    let error_type = options.error_type.is_some();
+    let streaming = options.streaming;
+    let return_type = options.return_type.as_ref();
+    let options = &options.types;
+    let mut docs = jsdoc::docs(attrs);
+    let mut args = vec![];
+    for arg in sig.inputs {
+        match arg {
+            // The receiver of a method is not passed by the frontend
+            arg if is_receiver(&arg) => {}
+            FnArg::Typed(arg) if types::is_injected(&arg.ty) => {}
+            FnArg::Typed(arg) => args.push(arg),
+            FnArg::Receiver(_) => unreachable!("Receivers are skipped"),
+        }
+    }
+    // Dates are emitted as strings, so their format is documented
+    for arg in &args {
+        if let (Some(format), Pat::Ident(pat)) = (types::date_format(&arg.ty), &*arg.pat) {
+            docs.push(format!("@param {} - {format}", pat.ident));
+        }
+    }
+    if let ReturnType::Type(_, ty) = &sig.output {
+        let ty = types::ok_type(ty);
+        if let Some(format) = types::date_format(ty) {
+            docs.push(format!("@returns {format}"));
+        }
+        // Elements of tuples are not named, so their format is documented by position
+        if let Type::Tuple(tuple) = ty {
+            let formats = tuple.elems.iter().enumerate().filter_map(|(index, elem)| Some(format!("[{index}]: {}", types::date_format(elem)?))).collect::<Vec<_>>();
+            if !formats.is_empty() {
+                docs.push(format!("@returns {}", formats.join(", ")));
+            }
+        }
+    }
+    let ok = match &sig.output {
+        ReturnType::Type(_, ty) => Some(types::ok_type(ty)),
+        ReturnType::Default => None,
+    };
+    let returned = match return_type {
+        Some(types) => types.iter().collect(),
+        None => ok.into_iter().collect::<Vec<_>>(),
+    };
+    let large_ints = args.iter().map(|arg| &*arg.ty).chain(returned)
+        .filter(|ty| options.large_int.is_none() && types::has_large_int(ty))
+        .cloned()
+        .collect();
+    let mut args = types(&args, options)?;
+    let stream = match streaming {
+        true => Some(stream(&sig.ident, &mut args)?),
+        false => None,
+    };
+    if !config::config()?.tauri_v2() && (stream.is_some() || args.iter().any(|(_, ty)| matches!(ty, TsType::Channel(_)))) {
+        return Err(syn::Error::new_spanned(&sig.ident, "Channels require Tauri v2, enable the feature `tauri-v2` or set `tauri_version = 2` in the `tauri-bindgen.toml`"));
+    }
+    let unresolved = ok.and_then(types::impl_trait).filter(|_| return_type.is_none()).cloned();
+    let ret = match return_type {
+        Some(types) => TsType::from_types(types, options),
+        None => TsType::from_return_type(&sig.output, options),
+    };
+    if let Some(ty) = args.iter().map(|(_, ty)| ty).chain([&ret]).find(|ty| ty.has_builtin("Uint8Array") && !is_binary(ty)) {
+        return Err(syn::Error::new_spanned(&sig.ident, format!("`{}` can not be declared with `bytes_as = \"Uint8Array\"`, which only applies to arguments and return types that are binary data themselves, i.e. `Vec<u8>` or `Option<Vec<u8>>`", ty.expand())));
+    }
+    if stream.as_ref().is_some_and(|(_, message)| message.has_builtin("Uint8Array")) {
+        return Err(syn::Error::new_spanned(&sig.ident, "Messages of a channel can not be declared with `bytes_as = \"Uint8Array\"`, as they are passed as array of numbers"));
+    }
+    let err = TsType::from_error_type(&sig.output, options);
+    if error_type && err.is_none() {
+        return Err(syn::Error::new_spanned(&sig.ident, "`error_type` can only be specified for commands returning `Result<T, E>`"));
+    }
+
+    let cfg = cfg_attributes(attrs);
+
+    Ok(Func { ident, name, invoke_name, docs, args, ret, err, unresolved, large_ints, cfg, stream })
+}
+
+/// Removes the `tauri::ipc::Channel<T>` argument of a streaming command from the arguments, returning its name and the type of its messages
+fn stream(ident: &Ident, args: &mut Vec<(Ident, TsType)>) -> syn::Result<(Ident, TsType)> {
+    let (channels, rest): (Vec<_>, Vec<_>) = args.drain(..).partition(|(_, ty)| matches!(ty, TsType::Channel(_)));
+    *args = rest;
+    match <[_; 1]>::try_from(channels) {
+        Ok([(channel, TsType::Channel(message))]) => Ok((channel, *message)),
+        Ok(_) => unreachable!("Only channels are partitioned"),
+        Err(channels) if channels.is_empty() => Err(syn::Error::new_spanned(ident, "Streaming commands need a `tauri::ipc::Channel<T>` argument through which they send their messages")),
+        Err(channels) => Err(syn::Error::new_spanned(&channels[1].0, "Streaming commands can only have one `tauri::ipc::Channel<T>` argument")),
+    }
+}
+
+/// ```#[cfg(...)]``` attributes of an annotated item, which are added to the generated items as well.
+/// These only take effect if they are placed below the attribute of this crate, as the item is removed before the attribute is expanded otherwise.
+pub(crate) fn cfg_attributes(attrs: &[Attribute]) -> Vec<Attribute> {
+    attrs.iter().filter(|attr| attr.path.is_ident("cfg")).cloned().collect()
+}
+
+/// Expands to the location of the item with the given span as `&str`, i.e. "src/lib.rs:12", which is added to its bindings
+pub(crate) fn source(span: proc_macro2::Span) -> proc_macro2::TokenStream {
+    quote_spanned! { span => concat!(file!(), ":", line!()) }
+}
+
+/// Proc macros can not emit warnings on stable Rust, so the warning is raised by using a deprecated constant at the given span
+pub(crate) fn warning(span: proc_macro2::Span, note: &str) -> proc_macro2::TokenStream {
+    quote_spanned! { span =>
+        const _: () = {
+            #[deprecated(note = #note)]
+            const WARNING: () = ();
+            WARNING
+        };
+    }
+}
+
+/// Key with which the command is invoked and name of the TypeScript function.
+/// Both default to the name of the command, which can be overridden separately with ```#[command(invoke_name = "...", ts_name = "...")]```.
+fn func_names(ident: &Ident, attrs: &[Attribute], options: &CommandOptions) -> syn::Result<(String, String)> {
+    let name = func_name(ident, attrs, options.rename.as_deref())?;
+    let invoke_name = options.invoke_name.clone().unwrap_or_else(|| name.clone());
+    let ts_name = options.ts_name.clone().unwrap_or(name);
+
+    // Renamed commands are wrapped in a function with their name, see `command_wrapper`
+    if *ident != invoke_name && syn::parse_str::<Ident>(&invoke_name).is_err() {
+        return Err(syn::Error::new(ident.span(), format!("`{invoke_name}` is not a valid name for a Tauri command, which needs to be the name of a Rust function as Tauri registers commands by it")));
+    }
+    if !is_identifier(&ts_name) {
+        return Err(syn::Error::new(ident.span(), format!("`{ts_name}` is not a valid name for a TypeScript function")));
+    }
+
+    Ok((invoke_name, ts_name))
+}
+
+/// Tauri passes binary data as JSON array of numbers, so the binding converts arguments and return types that are `Uint8Array` from and into `number[]`
+fn is_binary(ty: &TsType) -> bool {
+    match ty {
+        TsType::Nullable(inner) => is_binary(inner),
+        ty => matches!(ty, TsType::Builtin("Uint8Array")),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.starts_with(|c: char| c.is_ascii_digit()) && !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// The key is emitted as string literal, so it must not contain quotes or line breaks
+fn is_invoke_key(key: &str) -> bool {
+    !key.is_empty() && !key.contains(['\'', '\\', '\n'])
+}
+
+/// Permissions are emitted in JSDoc and in the capability file, so only the characters of identifiers such as "plugin:fs|read" or "fs:allow-read" are allowed
+fn is_permission(permission: &str) -> bool {
+    !permission.is_empty() && permission.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '|' | '.'))
+}
+
+/// Name of the command after applying ```#[command(rename = "...")]```, ```#[serde(rename = "...")]``` or ```#[serde(rename_all = "...")]```
+fn func_name(ident: &Ident, attrs: &[Attribute], rename: Option<&str>) -> syn::Result<String> {
+    match (rename, serde_attr::value(attrs, "rename"), serde_attr::value(attrs, "rename_all")) {
+        (Some(rename), _, _) => Ok(rename.to_owned()),
+        (None, Some(rename), _) => Ok(rename.value()),
+        (None, None, Some(rule)) => serde_attr::rename(&ident.to_string(), &rule.value())
+            .ok_or_else(|| syn::Error::new(rule.span(), format!("Unknown rename rule `{}`", rule.value()))),
+        (None, None, None) => Ok(ident.to_string()),
+    }
+}
+
+fn types(args: &[PatType], options: &TypeOptions) -> syn::Result<Vec<(Ident, TsType)>> {
     args.iter()
-        .map(|arg| (arg.pat.clone(), arg.ty.clone()))
-        .filter_map(|(pat, ty)| match *pat {
-            Pat::Ident(p) => Some((p, ty)),
-            _ => panic!("Only simple owned types are allowed as arguments at the moment!"),
-        })
-        .filter_map(|(pat, ty)| match *ty {
-            Type::Path(t) => Some((pat, t)),
-            _ => { panic!("Only simple owned types are allowed as arguments at the moment!") }
+        .map(|arg| match &*arg.pat {
+            Pat::Ident(pat) => Ok((pat.ident.clone(), TsType::from_type(&arg.ty, options))),
+            pat => Err(syn::Error::new_spanned(pat, "Only plain identifiers are allowed as argument names, as they are used as keys when invoking the command")),
         })
-        .map(|(pat, ty)| (pat.ident, ty.path))
         .collect()
 }
 
+/// Content of a generated command binding with placeholders for arguments (%0), payload (%1), return type (%2), imports (%3), error type (%4) and the type of streamed messages (%6)
+fn command_template(func: &Func, options: &CommandOptions) -> String {
+    let header = config::header();
+    let name = &func.name;
+    let invoke_key = options.invoke_key(&func.invoke_name);
+    let invoke = match &func.ret {
+        TsType::Builtin("Uint8Array") => format!("invoke<number[]>('{invoke_key}', {{ %1 }}).then((bytes) => new Uint8Array(bytes))"),
+        ret if is_binary(ret) => format!("invoke<number[] | null>('{invoke_key}', {{ %1 }}).then((bytes) => bytes && new Uint8Array(bytes))"),
+        _ => format!("invoke<%2>('{invoke_key}', {{ %1 }})"),
+    };
+    let binding = match (&func.stream, options.timeout_ms) {
+        (Some((channel, _)), _) => streaming_template(name, channel, &invoke),
+        // The timer is cleared once the command settles, so that it does not keep the event loop alive
+        (None, Some(ms)) => format!("export async function {name}(%0): Promise<%2> {{ let timer: ReturnType<typeof setTimeout> | undefined; const timeout = new Promise<never>((_, reject) => {{ timer = setTimeout(() => reject(new Error('Command `{name}` timed out after {ms} ms')), {ms}) }}); try {{ return await Promise.race([{invoke}, timeout]) }} finally {{ clearTimeout(timer) }} }}"),
+        (None, None) => format!("export async function {name}(%0): Promise<%2> {{ return await {invoke} }}"),
+    };
+
+    let mut docs = func.docs.clone();
+    docs.extend(options.acl.iter().map(|permission| format!("@permission {permission}")));
+    if func.err.is_some() {
+        docs.push("@throws {%4}".to_owned());
+    }
+    if let Some(ms) = options.timeout_ms {
+        docs.push(format!("@throws {{Error}} If the command does not settle within {ms} ms"));
+    }
+    let doc = jsdoc::jsdoc(&docs);
+    let debounced = options.debounce_ms.map(|ms| debounced_template(func, ms)).unwrap_or_default();
+    // `Channel` is part of the Tauri API even if `invoke` is imported from a wrapper, and only created by streaming commands
+    let channel = match (&func.stream, func.uses_channel()) {
+        (Some(_), _) => "\nimport { Channel } from \"@tauri-apps/api/core\"",
+        (None, true) => "\nimport type { Channel } from \"@tauri-apps/api/core\"",
+        (None, false) => "",
+    };
+
+    format!("{header}\n{}{channel}%3\n\n{doc}{binding}{debounced}", options.invoke_import)
+}
+
+/// Binding of a streaming command, which yields the messages sent through the channel until the command settles and then returns its result
+fn streaming_template(name: &str, channel: &Ident, invoke: &str) -> String {
+    let setup = format!("const messages: %6[] = []; let wake: (() => void) | undefined; let settled = false; const {channel} = new Channel<%6>(); {channel}.onmessage = (message) => {{ messages.push(message); wake?.() }}");
+    // Handling the settled promise right away keeps a rejection from being reported as unhandled while messages are still yielded
+    let invoke = format!("const result = {invoke}; const settle = () => {{ settled = true; wake?.() }}; result.then(settle, settle)");
+    let yields = "while (!settled || messages.length > 0) { if (messages.length > 0) { yield messages.shift() as %6 } else { await new Promise<void>((resolve) => { wake = resolve }) } }";
+
+    format!("export async function* {name}(%0): AsyncGenerator<%6, %2> {{ {setup}; {invoke}; {yields} return await result }}")
+}
+
+/// Variant of the binding that waits until it was not called for `ms` milliseconds, then invokes the command with the latest arguments.
+/// All calls in between resolve or reject with the result of that invocation.
+fn debounced_template(func: &Func, ms: u64) -> String {
+    let name = &func.name;
+    let args = func.args.iter().map(|(ident, _)| ident.to_string()).collect::<Vec<_>>().join(", ");
+    let state = format!("let {name}Timer: ReturnType<typeof setTimeout> | undefined\nlet {name}Pending: {{ resolve: (value: %2) => void, reject: (reason: unknown) => void }}[] = []");
+    let doc = jsdoc::jsdoc(&[format!("Same as `{name}`, but only invokes the command once it was not called for {ms} ms. All calls until then settle with the result of that invocation")]);
+    let binding = format!("export function {name}Debounced(%0): Promise<%2> {{ clearTimeout({name}Timer); return new Promise((resolve, reject) => {{ {name}Pending.push({{ resolve, reject }}); {name}Timer = setTimeout(() => {{ const pending = {name}Pending; {name}Pending = []; {name}({args}).then((value) => pending.forEach((call) => call.resolve(value)), (reason) => pending.forEach((call) => call.reject(reason))) }}, {ms}) }}) }}");
+
+    format!("\n\n{state}\n\n{doc}{binding}")
+}
+
+/// Directory of mocks relative to the generated binding.
+/// Jest and Vitest use the file with the same name in this directory when calling ```jest.mock("./greet")``` or ```vi.mock("./greet")```.
+const MOCK_DIR: &str = "__mocks__";
+
+/// Content of a mock of a command binding with the same placeholders as the binding itself, and the initial return value (%5)
+fn mock_template(func: &Func, debounced: bool) -> String {
+    let header = config::header();
+    let name = &func.name;
+    let unset = format!("if (returnValue === undefined) {{ throw new Error('No return value set for mocked command `{name}`, call setReturnValue first') }}");
+    // Streaming commands yield the given messages before they return
+    let (state, setter, binding) = match func.stream {
+        Some(_) => (
+            "let returnValue: { value: %2 } | undefined%5\nlet messages: %6[] = []",
+            format!("/**\n * Sets the value with which the mocked command `{name}` returns after yielding the given messages\n */\nexport function setReturnValue(value: %2, yielded: %6[] = []): void {{ returnValue = {{ value }}; messages = yielded }}"),
+            format!("export async function* {name}(..._args: [%0]): AsyncGenerator<%6, %2> {{ {unset} yield* messages; return returnValue.value }}"),
+        ),
+        None => (
+            "let returnValue: { value: %2 } | undefined%5",
+            format!("/**\n * Sets the value with which the mocked command `{name}` resolves\n */\nexport function setReturnValue(value: %2): void {{ returnValue = {{ value }} }}"),
+            format!("export async function {name}(..._args: [%0]): Promise<%2> {{ {unset} return returnValue.value }}"),
+        ),
+    };
+    let channel = match func.args.iter().any(|(_, ty)| matches!(ty, TsType::Channel(_))) {
+        true => "\nimport type { Channel } from \"@tauri-apps/api/core\"",
+        false => "",
+    };
+
+    // The debounced variant resolves immediately, so that tests do not need to wait for it
+    let debounced = match debounced {
+        true => format!("\n\nexport const {name}Debounced = {name}"),
+        false => String::new(),
+    };
+
+    format!("{header}{channel}%3\n\n{state}\n\n{setter}\n\n{binding}{debounced}")
+}
+
+/// Import prefix relative to the directory of mocks, unless it is not a relative path, i.e. an alias such as "@/models/"
+fn mock_import_prefix(import_prefix: &str) -> String {
+    match import_prefix.strip_prefix("./") {
+        Some(prefix) => format!("../{prefix}"),
+        None if import_prefix.starts_with("../") => format!("../{import_prefix}"),
+        None => import_prefix.to_owned(),
+    }
+}
+
 /// * `func`- An object that holds a functions metadata such as name and arguments
-/// * `dir` - Directory to which the resulting file will be exported
-fn generate_test(func: Func, dir: String) -> proc_macro2::TokenStream {
-    let Func { name, args } = func;
-    let arg_names = args.iter().map(|(ident, _)| ident.to_string()).collect::<Vec<_>>();
-    let arg_types = args.iter().map(|(_, path)| path).collect::<Vec<_>>();
+/// * `options` - Options passed to the attribute, such as the directory to which the resulting file will be exported
+fn generate_test(func: Func, options: &CommandOptions) -> proc_macro2::TokenStream {
+    descriptor::write_command(&func, options);
 
-    let test_fn = format_ident!("export_function_bindings_{}", name);
+    let content = command_template(&func, options);
+    let mock_content = mock_template(&func, options.debounce_ms.is_some());
+    let invoke_key = options.invoke_key(&func.invoke_name);
+    let payload = options.payload(&func.invoked_args());
+    let readme = options.readme.then(|| readme_entry(&func, options));
+    let Func { ident, name, args, ret, err, unresolved, large_ints, cfg, stream, .. } = func;
+    let message = stream.map(|(_, ty)| ty);
+    let CommandOptions { import_prefix, .. } = options;
+    let dir = options.out_dir();
+    let root = &options.dir;
+    let module = entity::module_index(root, options.module.as_deref());
+    let arg_names = args.iter().map(|(ident, _)| ident.to_string()).collect::<Vec<_>>();
+    let arg_types = args.iter().map(|(_, ty)| ty).collect::<Vec<_>>();
+    let entities = args.iter().map(|(_, ty)| ty).chain([&ret]).chain(&message).flat_map(TsType::entities).collect::<Vec<_>>();
 
-    let header = "// This file was generated by [tauri-bindgen-ts](https://github.com/antoniusnaumann/tauri-bindgen-ts). Do not edit this file manually.";
-    // TODO: Also import argument types
-    let import = "import { invoke } from \"@tauri-apps/api/tauri\"";
-    let binding = format!("export async function {name}(%0) {{ return await invoke('{name}', {{ %1 }}) }}");
+    let test_fn = format_ident!("export_function_bindings_{}", ident);
+    let source = source(ident.span());
 
     let file_name = format!("{dir}/{name}.ts");
-    let content = format!("{header}\n{import}\n\n{binding}");
+    let catch = match (&options.error_type, &err) {
+        (Some(error_type), Some(ty)) => error_guard(&dir, import_prefix, error_type, ty),
+        _ => quote! {},
+    };
+    // Error types are only mentioned in the documentation and may not implement ts_rs::TS
+    let err = match (options.error_type.as_deref(), err) {
+        (Some(error_type), _) => quote! { #error_type.to_owned() },
+        (None, Some(TsType::Entity(ty) | TsType::Generic(ty, _))) => quote! {{
+            use tauri_bindgen_ts::export::{ExportedName, NotExported};
+            (&tauri_bindgen_ts::export::Probe::<#ty>::default()).exported_name().unwrap_or_else(|| "unknown".to_owned())
+        }},
+        (None, Some(ty)) => quote! { #ty },
+        (None, None) => quote! { String::new() },
+    };
+    let mock = match options.mock {
+        true => {
+            let file_name = format!("{dir}/{MOCK_DIR}/{name}.ts");
+            let content = mock_content;
+            let import_prefix = mock_import_prefix(import_prefix);
+            quote! {
+                let imports = entities.iter().map(|(name, path)| match path {
+                    Some(path) => format!("\nimport type {{ {name} }} from \"{}\"", tauri_bindgen_ts::export::import_from_subdirectory(path)),
+                    None => format!("\nimport type {{ {name} }} from \"{}{name}\"", #import_prefix),
+                }).collect::<String>();
+                // Commands without return value resolve without setting one
+                let initial = if ret == "void" { " = { value: undefined }" } else { "" };
+                let content = #content.replace("%0", args.as_str()).replace("%2", ret.as_str()).replace("%3", imports.as_str()).replace("%5", initial).replace("%6", message.as_str());
+                tauri_bindgen_ts::export::write(#file_name, &tauri_bindgen_ts::export::add_metadata(&content, #source));
+            }
+        }
+        false => quote! {},
+    };
+    let durations = match args.iter().map(|(_, ty)| ty).chain([&ret]).chain(&message).any(TsType::has_duration) {
+        true => {
+            let dir = &options.dir;
+            quote! { tauri_bindgen_ts::export::add_duration_helpers(#dir); }
+        }
+        false => quote! {},
+    };
+
+    let warning = unresolved.map(|ty| {
+        let note = format!("The concrete type of `{}` is not known, so `{name}` resolves with `unknown`. Specify it with #[command(return_type = \"...\")]", ty.to_token_stream().to_string().replace(" :: ", "::"));
+        let warning = warning(ty.span(), &note);
+        quote! { #(#cfg)* #warning }
+    });
+
+    let large_int_warning = entity::large_int_warning(&large_ints.iter().collect::<Vec<_>>(), &cfg);
 
+    // Checked regardless of `cfg(test)`, so that missing bindings are reported by `cargo build` as well
+    let assertions = entities.iter().map(|ty| quote_spanned! { ty.span() =>
+        tauri_bindgen_ts::export::assert_binding::<#ty>();
+    });
+
+    let acl = &options.acl;
+    let permissions = (!acl.is_empty()).then(|| quote! { tauri_bindgen_ts::export::add_permissions(#root, &[#(#acl),*]); });
+    let message_name = match &message {
+        Some(message) => quote! { #message },
+        None => quote! { String::new() },
+    };
+
+    let setup = config::setup();
     quote! {
+        #warning
+        #large_int_warning
+        #(#cfg)*
+        const _: () = { #(#assertions)* };
+
+        #(#cfg)*
         #[cfg(test)]
         #[test]
         fn #test_fn() {
-            use std::fs;
+            #setup
             use tauri_bindgen_ts::ts_rs::TS;
 
-            let types = vec![#(#arg_types::name()),*];
-            let names = vec![#(#arg_names),*];
+            let types: Vec<String> = vec![#(#arg_types),*];
+            let ret: String = #ret;
+            let err: String = #err;
+            let message: String = #message_name;
+            let names: Vec<&str> = vec![#(#arg_names),*];
+            // Only types that are exported to their own file need to be imported, either from the export directory or from the path they specify
+            use tauri_bindgen_ts::export::{CustomImport, DefaultImport};
+            let dependencies: Vec<Option<(String, Option<&str>)>> = vec![#(
+                tauri_bindgen_ts::ts_rs::Dependency::from_ty::<#entities>().map(|dep| (dep.ts_name, (&tauri_bindgen_ts::export::Probe::<#entities>::default()).import_from()))
+            ),*];
+            let mut entities = dependencies.into_iter().flatten().collect::<Vec<_>>();
+            entities.sort();
+            entities.dedup();
+            let imports = entities.iter().map(|(name, path)| match path {
+                Some(path) => format!("\nimport type {{ {name} }} from \"{path}\""),
+                None => format!("\nimport type {{ {name} }} from \"{}{name}\"", #import_prefix),
+            }).collect::<String>();
             let args = types.iter().enumerate().map(|(index, elem)| [names[index].to_owned(), elem.to_owned()].join(": ")).collect::<Vec<String>>().join(", ");
 
-            fs::create_dir_all(#dir).expect("Could not create directory");
-            fs::write(#file_name, #content.replace("%0", args.as_str()).replace("%1", names.join(", ").as_str())).expect("Could not write generated function binding to file");
+            let content = #content.replace("%0", args.as_str()).replace("%1", #payload).replace("%2", ret.as_str()).replace("%3", imports.as_str()).replace("%4", err.as_str()).replace("%6", message.as_str());
+            tauri_bindgen_ts::export::write(#file_name, &tauri_bindgen_ts::export::add_metadata(&content, #source));
+            tauri_bindgen_ts::export::add_command_to_index(#dir, #name);
+            tauri_bindgen_ts::export::add_command_to_list(#root, #invoke_key);
+            #module
+            #mock
+            #durations
+            #catch
+            #readme
+            #permissions
+        }
+    }
+}
+
+/// Adds the signature and docs of the command to the overview in the export directory, resolving the same placeholders as the binding
+fn readme_entry(func: &Func, options: &CommandOptions) -> proc_macro2::TokenStream {
+    let name = &func.name;
+    let qualified = match &options.module {
+        Some(module) => format!("{module}/{name}"),
+        None => name.clone(),
+    };
+    let signature = match func.stream {
+        Some(_) => format!("{name}(%0): AsyncGenerator<%6, %2>"),
+        None => format!("{name}(%0): Promise<%2>"),
+    };
+    let mut docs = func.docs.clone();
+    if func.err.is_some() {
+        docs.extend(["".to_owned(), "Rejects with `%4`".to_owned()]);
+    }
+    let doc = docs.join("\n");
+    let root = &options.dir;
+
+    quote! {
+        let signature = #signature.replace("%0", args.as_str()).replace("%2", ret.as_str()).replace("%6", message.as_str());
+        tauri_bindgen_ts::export::add_command_to_readme(#root, #qualified, &signature, &#doc.replace("%4", err.as_str()));
+    }
+}
+
+/// Generates the type guard ```catchMyError``` for the error type of a command, which calls the guard of the error type if it is an entity with ```#[entity(guard)]```
+fn error_guard(dir: &str, import_prefix: &str, error_type: &str, ty: &TsType) -> proc_macro2::TokenStream {
+    match ty {
+        TsType::Entity(ty) | TsType::Generic(ty, _) => quote! {{
+            use tauri_bindgen_ts::export::{ExportedName, HasGuard, NoGuard, NotExported};
+            let probe = &tauri_bindgen_ts::export::Probe::<#ty>::default();
+            let check = "typeof e === \"object\" && e !== null";
+            match probe.exported_name() {
+                Some(name) => tauri_bindgen_ts::export::add_error_guard(#dir, #error_type, &name, Some(&format!("{}{name}", #import_prefix)), probe.has_guard(), check),
+                None => tauri_bindgen_ts::export::add_error_guard(#dir, #error_type, "object", None, false, check),
+            }
+        }},
+        TsType::Builtin(name @ ("string" | "number" | "boolean" | "bigint")) => {
+            let check = format!("typeof e === \"{name}\"");
+            quote! { tauri_bindgen_ts::export::add_error_guard(#dir, #error_type, #name, None, false, #check); }
         }
+        _ => quote! { tauri_bindgen_ts::export::add_error_guard(#dir, #error_type, "unknown", None, false, "e !== undefined"); },
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arguments(sig: Signature) -> Vec<(String, String)> {
+        let options = CommandOptions::from_args(&Args::default()).unwrap();
+        let func = func_metadata(sig, &[], &options).unwrap();
+        func.args.iter().map(|(ident, ty)| (ident.to_string(), ty.expand())).collect()
+    }
+
+    #[test]
+    fn state_is_omitted_from_the_arguments() {
+        let sig = parse_quote! { fn visit(state: State<'_, AppState>, counter: tauri::State<'_, Counter>, page: String) };
+
+        assert_eq!(arguments(sig), [("page".to_owned(), "string".to_owned())]);
+    }
+
+    #[test]
+    fn streaming_commands_create_their_channel() {
+        let sig: Signature = parse_quote! { fn download(url: String, on_progress: tauri::ipc::Channel<Progress>) };
+        let args = sig.inputs.iter().filter_map(|arg| match arg { FnArg::Typed(arg) => Some(arg.clone()), FnArg::Receiver(_) => None }).collect::<Vec<_>>();
+        let mut args = types(&args, &TypeOptions::default()).unwrap();
+
+        let (channel, message) = stream(&sig.ident, &mut args).unwrap();
+        assert_eq!((channel.to_string(), message.expand()), ("on_progress".to_owned(), "Progress".to_owned()));
+        assert_eq!(args.iter().map(|(ident, _)| ident.to_string()).collect::<Vec<_>>(), ["url"]);
+        assert!(stream(&sig.ident, &mut args).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn the_tauri_attribute_is_placed_before_or_after_the_other_attributes() {
+        let item: ItemFn = parse_quote! {
+            #[instrument]
+            #[allow(unused)]
+            fn greet() {}
+        };
+        let command: Attribute = parse_quote! { #[tauri::command] };
+        let paths = |item: &ItemFn| item.attrs.iter().map(|attr| attr.path.to_token_stream().to_string().replace(' ', "")).collect::<Vec<_>>();
+
+        let mut first = item.clone();
+        place_command(&mut first, command.clone(), false);
+        assert_eq!(paths(&first), ["tauri::command", "instrument", "allow"]);
+        let mut last = item;
+        place_command(&mut last, command, true);
+        assert_eq!(paths(&last), ["instrument", "allow", "tauri::command"]);
+    }
+
+    #[test]
+    fn renamed_commands_need_the_name_of_a_function() {
+        let names = |args: &str| {
+            let options = CommandOptions::from_args(&syn::parse_str(args).unwrap()).unwrap();
+            func_metadata(parse_quote! { fn get_user() }, &[], &options).map(|func| (func.invoke_name, func.name))
+        };
+
+        assert_eq!(names("rename = \"getUser\"").unwrap(), ("getUser".to_owned(), "getUser".to_owned()));
+        assert_eq!(names("ts_name = \"getUser\"").unwrap(), ("get_user".to_owned(), "getUser".to_owned()));
+        assert!(names("rename = \"get-user\"").is_err());
+        assert!(names("invoke_name = \"fn\"").is_err());
+    }
+
+    #[test]
+    fn only_binary_data_itself_is_declared_as_uint8_array() {
+        let options = CommandOptions::from_args(&syn::parse_str("bytes_as = \"Uint8Array\"").unwrap()).unwrap();
+
+        assert!(func_metadata(parse_quote! { fn upload(data: Vec<u8>) -> Option<Vec<u8>> }, &[], &options).is_ok());
+        assert!(func_metadata(parse_quote! { fn upload(chunks: Vec<Vec<u8>>) }, &[], &options).is_err());
+        assert!(func_metadata(parse_quote! { fn download() -> HashMap<String, Vec<u8>> }, &[], &options).is_err());
+    }
+
+    #[test]
+    fn entities_called_state_are_arguments() {
+        let sig = parse_quote! { fn transition(state: State, next: crate::machine::State) };
+
+        assert_eq!(arguments(sig), [("state".to_owned(), "State".to_owned()), ("next".to_owned(), "State".to_owned())]);
+    }
+}
@@ -0,0 +1,85 @@
+use syn::{Attribute, Lit, LitStr, Meta, NestedMeta};
+
+/// Value of a key-value pair inside a serde attribute, i.e. `"camelCase"` for `#[serde(rename_all = "camelCase")]`
+pub(crate) fn value(attrs: &[Attribute], key: &str) -> Option<LitStr> {
+    nested(attrs).into_iter().find_map(|meta| match meta {
+        Meta::NameValue(meta) if meta.path.is_ident(key) => match meta.lit {
+            Lit::Str(value) => Some(value),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Whether a flag such as `untagged` is present in a serde attribute, i.e. `#[serde(untagged)]`
+pub(crate) fn flag(attrs: &[Attribute], key: &str) -> bool {
+    nested(attrs).into_iter().any(|meta| matches!(meta, Meta::Path(path) if path.is_ident(key)))
+}
+
+/// Whether this is a serde attribute. These need to be removed from items that don't derive serde traits.
+pub(crate) fn is_serde(attr: &Attribute) -> bool {
+    attr.path.is_ident("serde")
+}
+
+fn nested(attrs: &[Attribute]) -> Vec<Meta> {
+    attrs.iter()
+        .filter(|attr| is_serde(attr))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::List(list)) => Some(list.nested),
+            _ => None,
+        })
+        .flatten()
+        .filter_map(|nested| match nested {
+            NestedMeta::Meta(meta) => Some(meta),
+            NestedMeta::Lit(_) => None,
+        })
+        .collect()
+}
+
+/// Applies a serde rename rule such as `camelCase` to a snake_case Rust identifier. Returns `None` for unknown rules.
+pub(crate) fn rename(name: &str, rule: &str) -> Option<String> {
+    let words = name.split('_').filter(|word| !word.is_empty()).collect::<Vec<_>>();
+    let capitalize = |word: &&str| {
+        let mut chars = word.chars();
+        chars.next().map(|first| first.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+    };
+
+    let renamed = match rule {
+        "lowercase" => name.to_lowercase(),
+        "UPPERCASE" => name.to_uppercase(),
+        "PascalCase" => words.iter().map(capitalize).collect(),
+        "camelCase" => words.first().map(|first| first.to_string()).into_iter().chain(words.iter().skip(1).map(capitalize)).collect(),
+        "snake_case" => name.to_owned(),
+        "SCREAMING_SNAKE_CASE" => name.to_uppercase(),
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.replace('_', "-").to_uppercase(),
+        _ => return None,
+    };
+
+    Some(renamed)
+}
+
+/// Applies a serde rename rule such as `camelCase` to a PascalCase enum variant. Returns `None` for unknown rules.
+pub(crate) fn rename_variant(name: &str, rule: &str) -> Option<String> {
+    let snake_case = || name.char_indices().fold(String::new(), |mut snake, (index, c)| {
+        if index > 0 && c.is_uppercase() {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+        snake
+    });
+
+    let renamed = match rule {
+        "lowercase" => name.to_lowercase(),
+        "UPPERCASE" => name.to_uppercase(),
+        "PascalCase" => name.to_owned(),
+        "camelCase" => name.chars().take(1).flat_map(char::to_lowercase).chain(name.chars().skip(1)).collect(),
+        "snake_case" => snake_case(),
+        "SCREAMING_SNAKE_CASE" => snake_case().to_uppercase(),
+        "kebab-case" => snake_case().replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => snake_case().replace('_', "-").to_uppercase(),
+        _ => return None,
+    };
+
+    Some(renamed)
+}
@@ -0,0 +1,690 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{Expr, ExprLit, GenericArgument, Lit, Path, PathArguments, ReturnType, Type, TypePath};
+
+use crate::args::Args;
+use crate::config;
+
+/// TypeScript representation of a Rust type used in a command signature
+#[derive(Clone)]
+pub(crate) enum TsType {
+    /// A builtin TypeScript type such as `string` or `number`
+    Builtin(&'static str),
+    /// A type specified by the user, i.e. the type of `serde_json::Value` given by ```json_value = "any"```
+    Custom(String),
+    /// A type implementing `ts_rs::TS`, i.e. a struct annotated with `#[entity]`. Its name is resolved when the generated test runs.
+    Entity(Box<Type>),
+    /// An entity with type arguments, i.e. `Page<User>`. The arguments are resolved the same way as any other type, so that these are imported as well.
+    Generic(Box<Type>, Vec<TsType>),
+    /// A type that can also be `null`, i.e. `Option<T>`
+    Nullable(Box<TsType>),
+    /// A sequence such as `Vec<T>`, which is serialized as JSON array
+    Array(Box<TsType>),
+    /// An array with a fixed length such as `[f32; 3]`, which is emitted as tuple type, i.e. `[number, number, number]`
+    FixedArray(Box<TsType>, usize),
+    /// A tuple such as `(u32, String)`, which is serialized as JSON array
+    Tuple(Vec<TsType>),
+    /// A sequence that is not meant to be mutated, i.e. a set when ```readonly_sets``` is enabled
+    ReadonlyArray(Box<TsType>),
+    /// A map such as `HashMap<K, V>`, which is serialized as JSON object
+    Record(Box<TsType>, Box<TsType>),
+    /// A channel through which the command sends messages of the given type, i.e. `tauri::ipc::Channel<T>`, which the frontend passes as `Channel<T>`
+    Channel(Box<TsType>),
+    /// Any of the given types, i.e. the documented shape of a command returning `serde_json::Value` given by ```return_type = "A | B"```
+    Union(Vec<TsType>),
+}
+
+/// Longer fixed-size arrays are emitted as plain array type, as spelling out the tuple type would not be readable anymore
+const MAX_TUPLE_LEN: usize = 16;
+
+/// Options that change how Rust types are mapped to TypeScript
+#[derive(Clone, Default)]
+pub(crate) struct TypeOptions {
+    /// Emit `ReadonlyArray<T>` instead of `T[]` for sets
+    pub(crate) readonly_sets: bool,
+    /// TypeScript type of `serde_json::Value`, defaults to `unknown`
+    pub(crate) json_value: Option<String>,
+    /// Emit `Uint8Array` instead of `number[]` for binary data such as `Vec<u8>`
+    pub(crate) bytes_as_uint8_array: bool,
+    /// Emit `number` for `std::time::Duration`, as it is serialized as milliseconds
+    pub(crate) duration_as_ms: bool,
+    /// Emit [NONZERO_BRAND] instead of `number` for `NonZero*` integers
+    pub(crate) branded_nonzero: bool,
+    /// TypeScript type of `i128` and `u128` given by ```large_int```, which are declared as `string` with a warning if it is not specified
+    pub(crate) large_int: Option<&'static str>,
+}
+
+impl TypeOptions {
+    /// TypeScript type of `i128` and `u128`, which a JavaScript number can not represent
+    pub(crate) fn large_int(&self) -> &'static str {
+        self.large_int.unwrap_or(if cfg!(feature = "bigint-int64") { "bigint" } else { "string" })
+    }
+}
+
+/// TypeScript type of 128-bit integers as specified by ```large_int = "bigint"``` or the feature "bigint-int64", if it is specified
+pub(crate) fn large_int(args: &Args) -> syn::Result<Option<&'static str>> {
+    match args.string("large_int")?.as_deref() {
+        None => Ok(cfg!(feature = "bigint-int64").then_some("bigint")),
+        Some("string") => Ok(Some("string")),
+        Some("bigint") => Ok(Some("bigint")),
+        Some(other) => Err(syn::Error::new(args.span("large_int").expect("Argument should be present"), format!("Unknown type `{other}` for 128-bit integers, expected \"string\" or \"bigint\""))),
+    }
+}
+
+/// TypeScript type of `i64` and `u64`, which exceed the safe integers of a JavaScript number
+const INT64: &str = if cfg!(feature = "bigint-int64") { "bigint" } else { "number" };
+
+/// Serde serializes `std::time::Duration` as object with whole seconds and remaining nanoseconds
+pub(crate) const DURATION: &str = "{ secs: number, nanos: number }";
+
+/// `NonZero*` integers are serialized as their underlying integer, the brand keeps plain numbers from being passed where zero is not allowed
+pub(crate) const NONZERO_BRAND: &str = "number & { readonly __nonZero: true }";
+
+impl TsType {
+    pub(crate) fn from_type(ty: &Type, options: &TypeOptions) -> TsType {
+        if let Some(mapped) = mapped_primitive(ty) {
+            return match mapped {
+                "string" => TsType::Builtin("string"),
+                "number" => TsType::Builtin("number"),
+                "boolean" => TsType::Builtin("boolean"),
+                "bigint" => TsType::Builtin("bigint"),
+                mapped => TsType::Custom(mapped.to_owned()),
+            };
+        }
+
+        if let Some(name) = builtin(ty) {
+            return TsType::Builtin(name);
+        }
+
+        if is_duration(ty) {
+            return TsType::Builtin(if options.duration_as_ms { "number" } else { DURATION });
+        }
+
+        // A JavaScript number can not represent all 128-bit integers, which therefore need to be serialized as string
+        let large_int = options.large_int();
+        if is_large_int(ty) {
+            return TsType::Builtin(large_int);
+        }
+
+        if let Some(integer) = nonzero(ty) {
+            return TsType::Builtin(match integer {
+                "u128" | "i128" => large_int,
+                "u64" | "i64" if cfg!(feature = "bigint-int64") => INT64,
+                _ if options.branded_nonzero => NONZERO_BRAND,
+                _ => "number",
+            });
+        }
+
+        if is_json_value(ty) {
+            return match &options.json_value {
+                Some(json_value) => TsType::Custom(json_value.clone()),
+                None => TsType::Builtin("unknown"),
+            };
+        }
+
+        if is_bytes(ty) {
+            return match options.bytes_as_uint8_array {
+                true => TsType::Builtin("Uint8Array"),
+                false => TsType::Array(Box::new(TsType::Builtin("number"))),
+            };
+        }
+
+        match ty {
+            // Borrowed arguments such as `&str` are deserialized the same way as their owned counterpart
+            Type::Reference(reference) => return TsType::from_type(&reference.elem, options),
+            Type::Slice(slice) => return TsType::Array(Box::new(TsType::from_type(&slice.elem, options))),
+            Type::Array(array) => {
+                let inner = Box::new(TsType::from_type(&array.elem, options));
+                // The length can only be determined if it is a literal, not if it is a constant
+                return match &array.len {
+                    Expr::Lit(ExprLit { lit: Lit::Int(len), .. }) => match len.base10_parse() {
+                        Ok(len) => TsType::FixedArray(inner, len),
+                        Err(_) => TsType::Array(inner),
+                    },
+                    _ => TsType::Array(inner),
+                };
+            }
+            Type::Paren(paren) => return TsType::from_type(&paren.elem, options),
+            Type::Tuple(tuple) => return TsType::Tuple(tuple.elems.iter().map(|elem| TsType::from_type(elem, options).unit_as_null()).collect()),
+            Type::Group(group) => return TsType::from_type(&group.elem, options),
+            // The concrete type of `impl Serialize` is not known to the macro
+            Type::ImplTrait(_) => return TsType::Builtin("unknown"),
+            _ => {}
+        }
+
+        if let Some(message) = channel(ty) {
+            return TsType::Channel(Box::new(TsType::from_type(message, options)));
+        }
+
+        let (name, args) = generic(ty).unwrap_or_default();
+        match (name.as_str(), args.as_slice()) {
+            // Nested options collapse because serde serializes both `None` and `Some(None)` as null
+            ("Option", [inner]) => match TsType::from_type(inner, options) {
+                TsType::Nullable(inner) => TsType::Nullable(inner),
+                inner => TsType::Nullable(Box::new(inner)),
+            },
+            // Smart pointers and locks have the same serialized representation as the type they wrap, i.e. `Arc<Mutex<T>>` is serialized as `T`
+            ("Box" | "Arc" | "Rc" | "Cow" | "Mutex" | "RwLock" | "RefCell" | "Cell", [inner]) => TsType::from_type(inner, options),
+            ("Vec" | "VecDeque" | "LinkedList", [inner]) => TsType::Array(Box::new(TsType::from_type(inner, options))),
+            // Sets are serialized as JSON array as well but are usually not mutated on the frontend
+            ("HashSet" | "BTreeSet", [inner]) if options.readonly_sets => TsType::ReadonlyArray(Box::new(TsType::from_type(inner, options))),
+            ("HashSet" | "BTreeSet", [inner]) => TsType::Array(Box::new(TsType::from_type(inner, options))),
+            ("HashMap" | "BTreeMap" | "IndexMap", [key, value]) => TsType::Record(Box::new(TsType::from_type(key, options)), Box::new(TsType::from_type(value, options))),
+            (_, []) => TsType::Entity(Box::new(ty.clone())),
+            (_, args) => TsType::Generic(Box::new(ty.clone()), args.iter().map(|arg| TsType::from_type(arg, options)).collect()),
+        }
+    }
+
+    /// Resolves each of the given types, which are declared as union if there are several
+    pub(crate) fn from_types(types: &[Type], options: &TypeOptions) -> TsType {
+        match types {
+            [ty] => TsType::from_type(ty, options),
+            types => TsType::Union(types.iter().map(|ty| TsType::from_type(ty, options).unit_as_null()).collect()),
+        }
+    }
+
+    /// Functions without an explicit return type as well as `-> ()` resolve to `void`.
+    /// The signature of an `async fn` declares the type of its output and not the future, so it does not need to be unwrapped.
+    /// For commands returning `Result<T, E>` or `tauri::Result<T>`, this is the type `T` with which the promise resolves.
+    pub(crate) fn from_return_type(output: &ReturnType, options: &TypeOptions) -> TsType {
+        match output {
+            ReturnType::Default => TsType::Builtin("void"),
+            ReturnType::Type(_, ty) => TsType::from_type(ok_type(ty), options),
+        }
+    }
+
+    /// The error type `E` with which the promise rejects, for commands returning `Result<T, E>`.
+    /// `tauri::Error` is serialized as its error message, so commands returning `tauri::Result<T>` reject with a string.
+    pub(crate) fn from_error_type(output: &ReturnType, options: &TypeOptions) -> Option<TsType> {
+        match output {
+            ReturnType::Default => None,
+            ReturnType::Type(_, ty) if tauri_result(ty).is_some() => Some(TsType::Builtin("string")),
+            ReturnType::Type(_, ty) => result(ty).map(|(_, err)| TsType::from_type(err, options)),
+        }
+    }
+
+    /// Rust types referenced by this type that may need to be imported in the generated binding
+    pub(crate) fn entities(&self) -> Vec<&Type> {
+        match self {
+            TsType::Builtin(_) | TsType::Custom(_) => vec![],
+            TsType::Entity(ty) => vec![ty],
+            TsType::Generic(ty, args) => [&**ty].into_iter().chain(args.iter().flat_map(TsType::entities)).collect(),
+            TsType::Nullable(inner) | TsType::Array(inner) | TsType::FixedArray(inner, _) | TsType::ReadonlyArray(inner) | TsType::Channel(inner) => inner.entities(),
+            TsType::Tuple(elems) | TsType::Union(elems) => elems.iter().flat_map(TsType::entities).collect(),
+            TsType::Record(key, value) => [key.entities(), value.entities()].concat(),
+        }
+    }
+
+    /// Entities whose type guard or schema is used for this type, which excludes generic entities and their type arguments
+    pub(crate) fn referenced_entities(&self) -> Vec<&Type> {
+        match self {
+            TsType::Builtin(_) | TsType::Custom(_) | TsType::Generic(..) => vec![],
+            TsType::Entity(ty) => vec![ty],
+            TsType::Nullable(inner) | TsType::Array(inner) | TsType::FixedArray(inner, _) | TsType::ReadonlyArray(inner) | TsType::Channel(inner) => inner.referenced_entities(),
+            TsType::Tuple(elems) | TsType::Union(elems) => elems.iter().flat_map(TsType::referenced_entities).collect(),
+            // Keys are serialized as strings
+            TsType::Record(_, value) => value.referenced_entities(),
+        }
+    }
+
+    /// Resolves the TypeScript type during macro expansion. As ts_rs cannot be queried at this point, entity types use the name of their Rust type.
+    pub(crate) fn expand(&self) -> String {
+        self.expand_with(&entity_name)
+    }
+
+    /// Same as [TsType::expand], but entity types are named by `name`, i.e. with a placeholder that is resolved once their TypeScript name is known
+    pub(crate) fn expand_with(&self, name: &dyn Fn(&Type) -> String) -> String {
+        let expand = |ty: &TsType| ty.expand_with(name);
+        match self {
+            TsType::Builtin(name) => name.to_string(),
+            TsType::Custom(name) => name.clone(),
+            TsType::Entity(ty) => name(ty),
+            TsType::Generic(ty, args) => format!("{}<{}>", name(ty), args.iter().map(expand).collect::<Vec<_>>().join(", ")),
+            TsType::Nullable(inner) => format!("{} | null", expand(inner)),
+            TsType::Array(inner) if inner.is_union() => format!("({})[]", expand(inner)),
+            TsType::Array(inner) => format!("{}[]", expand(inner)),
+            TsType::FixedArray(inner, len) if *len > MAX_TUPLE_LEN => format!("{} /* length: {len} */", expand(&TsType::Array(inner.clone()))),
+            TsType::FixedArray(inner, len) => format!("[{}]", vec![expand(inner); *len].join(", ")),
+            TsType::Tuple(elems) => format!("[{}]", elems.iter().map(expand).collect::<Vec<_>>().join(", ")),
+            TsType::ReadonlyArray(inner) => format!("ReadonlyArray<{}>", expand(inner)),
+            TsType::Record(key, value) => format!("Record<{}, {}>", expand(key), expand(value)),
+            TsType::Channel(inner) => format!("Channel<{}>", expand(inner)),
+            TsType::Union(elems) => elems.iter().map(expand).collect::<Vec<_>>().join(" | "),
+        }
+    }
+
+    /// Whether this type contains the serialized form of `std::time::Duration`, for which helpers are generated
+    pub(crate) fn has_duration(&self) -> bool {
+        self.has_builtin(DURATION)
+    }
+
+    /// Whether this type contains the given builtin type, i.e. `Uint8Array`
+    pub(crate) fn has_builtin(&self, builtin: &str) -> bool {
+        match self {
+            TsType::Builtin(name) => *name == builtin,
+            TsType::Custom(_) | TsType::Entity(_) => false,
+            TsType::Generic(_, args) | TsType::Tuple(args) | TsType::Union(args) => args.iter().any(|arg| arg.has_builtin(builtin)),
+            TsType::Nullable(inner) | TsType::Array(inner) | TsType::FixedArray(inner, _) | TsType::ReadonlyArray(inner) | TsType::Channel(inner) => inner.has_builtin(builtin),
+            TsType::Record(key, value) => key.has_builtin(builtin) || value.has_builtin(builtin),
+        }
+    }
+
+    /// The unit type is only `void` as return type, serde serializes it as `null` when nested in another type
+    fn unit_as_null(self) -> TsType {
+        match self {
+            TsType::Builtin("void") => TsType::Builtin("null"),
+            ty => ty,
+        }
+    }
+
+    /// Union and intersection types need to be wrapped in parentheses when used as element type
+    fn is_union(&self) -> bool {
+        matches!(self, TsType::Nullable(_) | TsType::Union(_) | TsType::Builtin(NONZERO_BRAND))
+    }
+}
+
+/// Expands to an expression that evaluates to the TypeScript type name as `String`
+impl ToTokens for TsType {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(match self {
+            TsType::Builtin(name) => quote! { #name.to_owned() },
+            TsType::Custom(name) => quote! { #name.to_owned() },
+            TsType::Entity(ty) => quote! { <#ty as tauri_bindgen_ts::ts_rs::TS>::name() },
+            TsType::Generic(ty, args) => quote! { <#ty as tauri_bindgen_ts::ts_rs::TS>::name_with_type_args(vec![#(#args),*]) },
+            TsType::Nullable(inner) => quote! { format!("{} | null", #inner) },
+            TsType::Array(inner) if inner.is_union() => quote! { format!("({})[]", #inner) },
+            TsType::Array(inner) => quote! { format!("{}[]", #inner) },
+            TsType::FixedArray(inner, len) if *len > MAX_TUPLE_LEN => {
+                let array = TsType::Array(inner.clone());
+                quote! { format!("{} /* length: {} */", #array, #len) }
+            }
+            TsType::FixedArray(inner, len) => quote! { format!("[{}]", vec![#inner; #len].join(", ")) },
+            TsType::Tuple(elems) => quote! { format!("[{}]", [#(#elems),*].join(", ")) },
+            TsType::ReadonlyArray(inner) => quote! { format!("ReadonlyArray<{}>", #inner) },
+            TsType::Record(key, value) => quote! { format!("Record<{}, {}>", #key, #value) },
+            TsType::Channel(inner) => quote! { format!("Channel<{}>", #inner) },
+            TsType::Union(elems) => quote! { [#(#elems),*].join(" | ") },
+        })
+    }
+}
+
+/// Argument types that are provided by Tauri when a command is invoked and therefore never passed from the frontend
+const INJECTED: &[&str] = &["AppHandle", "Window", "WebviewWindow", "Webview"];
+
+/// Checks whether the given argument type is injected by Tauri, i.e. `tauri::AppHandle` or `State<'_, MyState>`.
+/// Types are recognized with their `tauri::` path or imported by their plain name, i.e. `AppHandle` after `use tauri::AppHandle`.
+/// Other paths such as `crate::models::Window` are entities, so that an entity with the name of a Tauri type can still be passed by its path.
+/// `State` is recognized by its last path segment, but only with a single type parameter, so that an entity called `State` can still be passed from the frontend.
+pub(crate) fn is_injected(ty: &Type) -> bool {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return false };
+    let segments = path.segments.iter().map(|segment| segment.ident.to_string()).collect::<Vec<_>>();
+
+    match segments.as_slice() {
+        [name] if INJECTED.contains(&name.as_str()) => true,
+        [tauri, .., last] if tauri == "tauri" && INJECTED.contains(&last.as_str()) => true,
+        [.., last] if last == "State" => is_state(path),
+        // Tauri v2 allows accessing the raw IPC request
+        [tauri, .., ipc, last] => tauri == "tauri" && ipc == "ipc" && last == "Request",
+        _ => false,
+    }
+}
+
+/// Whether the last segment has exactly one type argument, i.e. `State<'_, MyState>`
+fn is_state(path: &Path) -> bool {
+    let Some(PathArguments::AngleBracketed(args)) = path.segments.last().map(|segment| &segment.arguments) else { return false };
+    args.args.iter().filter(|arg| matches!(arg, GenericArgument::Type(_))).count() == 1
+}
+
+/// Type of the messages sent through `tauri::ipc::Channel<T>`, which needs to be qualified with `tauri::` same as injected types
+fn channel(ty: &Type) -> Option<&Type> {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return None };
+    let (Some(first), Some(last)) = (path.segments.first(), path.segments.last()) else { return None };
+    if first.ident != "tauri" || last.ident != "Channel" {
+        return None;
+    }
+
+    match &last.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.iter().collect::<Vec<_>>().as_slice() {
+            [GenericArgument::Type(message)] => Some(message),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Name of the last path segment of a type, i.e. `User` for `crate::models::User`
+pub(crate) fn entity_name(ty: &Type) -> String {
+    match ty {
+        Type::Path(TypePath { path, .. }) => path.segments.last().map(|segment| segment.ident.to_string()).unwrap_or_default(),
+        _ => ty.to_token_stream().to_string(),
+    }
+}
+
+/// Description of the format in which a date or time type from chrono is serialized. Requires the feature `chrono`.
+pub(crate) fn date_format(ty: &Type) -> Option<&'static str> {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return None };
+    if !cfg!(feature = "chrono") {
+        return None;
+    }
+
+    match () {
+        _ if path_is(path, &["chrono", "DateTime"]) => Some("ISO 8601 date and time, i.e. \"2024-01-31T12:00:00Z\""),
+        _ if path_is(path, &["chrono", "NaiveDateTime"]) => Some("ISO 8601 date and time without time zone, i.e. \"2024-01-31T12:00:00\""),
+        _ if path_is(path, &["chrono", "NaiveDate"]) => Some("ISO 8601 date, i.e. \"2024-01-31\""),
+        _ if path_is(path, &["chrono", "NaiveTime"]) => Some("ISO 8601 time, i.e. \"12:00:00\""),
+        _ => None,
+    }
+}
+
+/// Whether the path refers to the given type, either fully qualified such as `uuid::Uuid` or by its name `Uuid` only, assuming it is imported
+fn path_is(path: &Path, qualified: &[&str]) -> bool {
+    path.segments.len() <= qualified.len() && path.segments.iter().rev().zip(qualified.iter().rev()).all(|(segment, name)| segment.ident == name)
+}
+
+/// `std::marker::PhantomData<T>`, also when imported
+pub(crate) fn is_phantom_data(ty: &Type) -> bool {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return false };
+    path.segments.last().is_some_and(|segment| segment.ident == "PhantomData")
+}
+
+/// `std::time::Duration`, also when imported. `chrono::Duration` is handled by ts_rs instead.
+pub(crate) fn is_duration(ty: &Type) -> bool {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return false };
+    path_is(path, &["std", "time", "Duration"]) || path_is(path, &["core", "time", "Duration"])
+}
+
+/// Binary data, which serde serializes as sequence of numbers
+fn is_bytes(ty: &Type) -> bool {
+    let is_u8 = |ty: &Type| matches!(ty, Type::Path(TypePath { qself: None, path }) if path.is_ident("u8"));
+    match ty {
+        Type::Reference(reference) => is_bytes(&reference.elem),
+        Type::Slice(slice) => is_u8(&slice.elem),
+        Type::Path(TypePath { qself: None, path }) if path_is(path, &["bytes", "Bytes"]) => true,
+        _ => match generic(ty) {
+            Some((name, args)) if name == "Vec" && args.len() == 1 => is_u8(args[0]),
+            Some((name, args)) if matches!(name.as_str(), "Box" | "Arc" | "Rc") && args.len() == 1 => matches!(args[0], Type::Slice(slice) if is_u8(&slice.elem)),
+            _ => false,
+        },
+    }
+}
+
+/// Dynamic JSON values can be anything, so they are not typed more specifically.
+/// `Value` is assumed to be imported from serde_json, as it can not be resolved by ts_rs either way.
+fn is_json_value(ty: &Type) -> bool {
+    matches!(ty, Type::Path(TypePath { qself: None, path }) if path_is(path, &["serde_json", "Value"]))
+}
+
+/// Whether the type is `Option<T>`. As ts_rs only declares optional fields for unqualified options, `std::option::Option<T>` is not matched.
+pub(crate) fn is_option(ty: &Type) -> bool {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return false };
+    path.leading_colon.is_none() && path.segments.len() == 1 && matches!(generic(ty), Some((name, args)) if name == "Option" && args.len() == 1)
+}
+
+/// Inner type of `Option<T>`
+pub(crate) fn option_inner(ty: &Type) -> Option<&Type> {
+    match generic(ty)? {
+        (name, args) if name == "Option" && args.len() == 1 => Some(args[0]),
+        _ => None,
+    }
+}
+
+/// Finds an `impl Trait` in the type, i.e. in `Vec<impl Serialize>`, whose concrete type can not be resolved
+pub(crate) fn impl_trait(ty: &Type) -> Option<&Type> {
+    find(ty, &|ty| matches!(ty, Type::ImplTrait(_)))
+}
+
+/// Whether the type contains a `NonZero*` integer, i.e. `Vec<NonZeroU32>`
+pub(crate) fn has_nonzero(ty: &Type) -> bool {
+    find(ty, &|ty| nonzero(ty).is_some()).is_some()
+}
+
+/// Whether the type contains a 128-bit integer, i.e. `Vec<u128>` or `NonZeroI128`
+pub(crate) fn has_large_int(ty: &Type) -> bool {
+    find(ty, &|ty| is_large_int(ty) || matches!(nonzero(ty), Some("u128" | "i128"))).is_some()
+}
+
+fn is_large_int(ty: &Type) -> bool {
+    matches!(ty, Type::Path(TypePath { qself: None, path }) if path.is_ident("u128") || path.is_ident("i128"))
+}
+
+/// Whether the type contains a primitive whose mapping is overridden in the ```[type_map]``` of the config, i.e. `Vec<i64>`
+pub(crate) fn has_mapped_primitive(ty: &Type) -> bool {
+    find(ty, &|ty| mapped_primitive(ty).is_some()).is_some()
+}
+
+/// TypeScript type of a primitive as configured in the ```[type_map]```. Errors are ignored, as these are already reported when reading the options of the attribute.
+fn mapped_primitive(ty: &Type) -> Option<&'static str> {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return None };
+    config::config().ok()?.mapped_type(&path.get_ident()?.to_string())
+}
+
+/// Finds the first type for which `matches` holds, searching the type itself first and then the types it consists of
+fn find<'a>(ty: &'a Type, matches: &impl Fn(&Type) -> bool) -> Option<&'a Type> {
+    if matches(ty) {
+        return Some(ty);
+    }
+
+    match ty {
+        Type::Reference(reference) => find(&reference.elem, matches),
+        Type::Slice(slice) => find(&slice.elem, matches),
+        Type::Array(array) => find(&array.elem, matches),
+        Type::Paren(paren) => find(&paren.elem, matches),
+        Type::Group(group) => find(&group.elem, matches),
+        Type::Tuple(tuple) => tuple.elems.iter().find_map(|elem| find(elem, matches)),
+        _ => generic(ty)?.1.into_iter().find_map(|arg| find(arg, matches)),
+    }
+}
+
+/// Integer type wrapped by `NonZeroU32` and the other `NonZero*` integers of `std::num`, including the generic `NonZero<u32>`
+fn nonzero(ty: &Type) -> Option<&'static str> {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return None };
+    let is_num = |name: &str| path.segments.len() == 1 || path_is(path, &["std", "num", name]) || path_is(path, &["core", "num", name]);
+    let segment = path.segments.last()?;
+    let name = segment.ident.to_string();
+    if !name.starts_with("NonZero") || !is_num(&name) {
+        return None;
+    }
+
+    let integer = match generic(ty) {
+        Some((_, args)) if name == "NonZero" && args.len() == 1 => args[0].to_token_stream().to_string(),
+        _ => name.strip_prefix("NonZero")?.to_lowercase(),
+    };
+    ["u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize"].into_iter().find(|name| *name == integer)
+}
+
+/// Splits `Result<T, E>` into its ok and error type
+pub(crate) fn result(ty: &Type) -> Option<(&Type, &Type)> {
+    match generic(ty)? {
+        (name, args) if name == "Result" && args.len() == 2 => Some((args[0], args[1])),
+        _ => None,
+    }
+}
+
+/// Inner type of `tauri::Result<T>`, which is an alias of `Result<T, tauri::Error>`. Only the qualified path is recognized, as other crates declare their own `Result<T>` as well.
+pub(crate) fn tauri_result(ty: &Type) -> Option<&Type> {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return None };
+    if path.segments.len() != 2 || path.segments[0].ident != "tauri" {
+        return None;
+    }
+
+    match generic(ty)? {
+        (name, args) if name == "Result" && args.len() == 1 => Some(args[0]),
+        _ => None,
+    }
+}
+
+/// The type with which a command resolves, i.e. `T` for `Result<T, E>` and `tauri::Result<T>`
+pub(crate) fn ok_type(ty: &Type) -> &Type {
+    match (result(ty), tauri_result(ty)) {
+        (Some((ok, _)), _) | (None, Some(ok)) => ok,
+        (None, None) => ty,
+    }
+}
+
+/// Splits a generic type such as `Option<T>` into the name of its last path segment and its type arguments
+fn generic(ty: &Type) -> Option<(String, Vec<&Type>)> {
+    let Type::Path(TypePath { qself: None, path }) = ty else { return None };
+    let segment = path.segments.last()?;
+    let PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    let types = args.args.iter()
+        .filter_map(|arg| match arg { GenericArgument::Type(ty) => Some(ty), _ => None })
+        .collect();
+
+    Some((segment.ident.to_string(), types))
+}
+
+fn builtin(ty: &Type) -> Option<&'static str> {
+    match ty {
+        Type::Tuple(tuple) if tuple.elems.is_empty() => Some("void"),
+        // UUIDs are serialized in their hyphenated string representation
+        Type::Path(TypePath { qself: None, path }) if path_is(path, &["uuid", "Uuid"]) => Some("string"),
+        _ if date_format(ty).is_some() => Some("string"),
+        Type::Path(TypePath { qself: None, path }) => match path.get_ident()?.to_string().as_str() {
+            "String" | "str" | "char" => Some("string"),
+            "bool" => Some("boolean"),
+            "u8" | "u16" | "u32" | "usize" | "i8" | "i16" | "i32" | "isize" | "f32" | "f64" => Some("number"),
+            "u64" | "i64" => Some(INT64),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn expand(ty: Type) -> String {
+        TsType::from_type(&ty, &TypeOptions::default()).expand()
+    }
+
+    #[test]
+    fn sequences_are_arrays() {
+        assert_eq!(expand(parse_quote! { Vec<String> }), "string[]");
+        assert_eq!(expand(parse_quote! { std::collections::VecDeque<u32> }), "number[]");
+        assert_eq!(expand(parse_quote! { LinkedList<Vec<bool>> }), "boolean[][]");
+        assert_eq!(expand(parse_quote! { Vec<Option<u8>> }), "(number | null)[]");
+    }
+
+    #[test]
+    fn optional_sequences_are_nullable_arrays() {
+        assert_eq!(expand(parse_quote! { Option<Vec<String>> }), "string[] | null");
+        assert_eq!(expand(parse_quote! { Option<Vec<Option<String>>> }), "(string | null)[] | null");
+        assert_eq!(expand(parse_quote! { Option<Vec<Option<Vec<Option<u8>>>>> }), "((number | null)[] | null)[] | null");
+    }
+
+    #[test]
+    fn entities_in_sequences_are_imported() {
+        let ty = TsType::from_type(&parse_quote! { Vec<crate::models::User> }, &TypeOptions::default());
+
+        assert_eq!(ty.expand(), "User[]");
+        assert_eq!(ty.entities(), [&parse_quote! { crate::models::User } as &Type]);
+    }
+
+    #[test]
+    fn nested_collections_are_resolved_recursively() {
+        let ty = TsType::from_type(&parse_quote! { Vec<HashMap<String, Option<User>>> }, &TypeOptions::default());
+
+        assert_eq!(ty.expand(), "Record<string, User | null>[]");
+        assert_eq!(ty.entities(), [&parse_quote! { User } as &Type]);
+        assert_eq!(expand(parse_quote! { HashMap<String, Vec<BTreeMap<u32, Vec<Option<String>>>>> }), "Record<string, Record<number, (string | null)[]>[]>");
+    }
+
+    #[test]
+    fn several_types_are_declared_as_union() {
+        let ty = TsType::from_types(&[parse_quote! { User }, parse_quote! { Vec<Settings> }, parse_quote! { () }], &TypeOptions::default());
+
+        assert_eq!(ty.expand(), "User | Settings[] | null");
+        assert_eq!(ty.entities(), [&parse_quote! { User } as &Type, &parse_quote! { Settings }]);
+        assert_eq!(TsType::Array(Box::new(ty)).expand(), "(User | Settings[] | null)[]");
+    }
+
+    #[test]
+    fn results_without_data_resolve_with_void() {
+        let options = TypeOptions::default();
+        let outputs: [ReturnType; 3] = [parse_quote! { -> Result<(), MyError> }, parse_quote! { -> std::result::Result<(), String> }, parse_quote! { -> tauri::Result<()> }];
+        for output in outputs {
+            assert_eq!(TsType::from_return_type(&output, &options).expand(), "void");
+        }
+        assert_eq!(TsType::from_error_type(&parse_quote! { -> Result<(), MyError> }, &options).map(|err| err.expand()).as_deref(), Some("MyError"));
+    }
+
+    #[test]
+    fn references_are_declared_as_the_owned_type() {
+        assert_eq!(expand(parse_quote! { &str }), "string");
+        assert_eq!(expand(parse_quote! { &'a str }), "string");
+        assert_eq!(expand(parse_quote! { &[u8] }), "number[]");
+        assert_eq!(expand(parse_quote! { &[String] }), "string[]");
+        assert_eq!(expand(parse_quote! { &'a Vec<User> }), "User[]");
+    }
+
+    #[test]
+    fn borrowed_or_owned_values_are_declared_as_the_owned_type() {
+        assert_eq!(expand(parse_quote! { Cow<'_, str> }), "string");
+        assert_eq!(expand(parse_quote! { std::borrow::Cow<'static, str> }), "string");
+        assert_eq!(expand(parse_quote! { Cow<'a, [u32]> }), "number[]");
+        assert_eq!(expand(parse_quote! { Cow<'_, [Option<User>]> }), "(User | null)[]");
+        assert_eq!(expand(parse_quote! { Option<Cow<'_, str>> }), "string | null");
+    }
+
+    #[test]
+    fn large_integers_are_strings_unless_declared_as_bigint() {
+        let bigint = TypeOptions { large_int: Some("bigint"), ..TypeOptions::default() };
+        let declared = if cfg!(feature = "bigint-int64") { "bigint" } else { "string" };
+
+        assert_eq!(expand(parse_quote! { u128 }), declared);
+        assert_eq!(expand(parse_quote! { Vec<std::num::NonZeroI128> }), format!("{declared}[]"));
+        assert_eq!(expand(parse_quote! { (u64, NonZeroI64) }), format!("[{INT64}, {INT64}]"));
+        assert_eq!(TsType::from_type(&parse_quote! { Option<i128> }, &bigint).expand(), "bigint | null");
+        assert_eq!(TsType::from_type(&parse_quote! { NonZeroU128 }, &bigint).expand(), "bigint");
+        assert!(large_int(&syn::parse_str("large_int = \"number\"").unwrap()).is_err());
+    }
+
+    #[test]
+    fn uuids_are_strings() {
+        assert_eq!(expand(parse_quote! { uuid::Uuid }), "string");
+        assert_eq!(expand(parse_quote! { Uuid }), "string");
+        assert_eq!(expand(parse_quote! { Option<Vec<::uuid::Uuid>> }), "string[] | null");
+        assert_eq!(expand(parse_quote! { other::Uuid }), "Uuid");
+    }
+
+    #[test]
+    fn channels_are_passed_with_their_message_type() {
+        let ty = TsType::from_type(&parse_quote! { tauri::ipc::Channel<Vec<Progress>> }, &TypeOptions::default());
+
+        assert_eq!(ty.expand(), "Channel<Progress[]>");
+        assert_eq!(ty.entities(), [&parse_quote! { Progress } as &Type]);
+        // Without the `tauri::` path, the type is an entity that happens to be called `Channel`
+        assert!(matches!(TsType::from_type(&parse_quote! { Channel<Progress> }, &TypeOptions::default()), TsType::Generic(..)));
+    }
+
+    #[test]
+    fn tauri_types_are_injected() {
+        let injected: [Type; 8] = [
+            parse_quote! { tauri::AppHandle },
+            parse_quote! { AppHandle },
+            parse_quote! { WebviewWindow<R> },
+            parse_quote! { tauri::Window<R> },
+            parse_quote! { tauri::webview::WebviewWindow },
+            parse_quote! { tauri::ipc::Request<'_> },
+            parse_quote! { State<'_, AppState> },
+            parse_quote! { tauri::State<'_, AppState> },
+        ];
+
+        assert!(injected.iter().all(is_injected));
+    }
+
+    #[test]
+    fn entities_with_the_name_of_tauri_types_are_not_injected() {
+        let entities: [Type; 5] = [
+            parse_quote! { self::Window },
+            parse_quote! { crate::models::Webview },
+            parse_quote! { Manager },
+            parse_quote! { State },
+            parse_quote! { Request },
+        ];
+
+        assert!(!entities.iter().any(is_injected));
+    }
+}
@@ -0,0 +1,129 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Field, Fields, LitStr, Type};
+
+use crate::types::{TsType, TypeOptions, DURATION, NONZERO_BRAND};
+use crate::{config, entity, serde_attr};
+
+/// Creates an expression that evaluates to the content of a file declaring a Zod schema for the entity, i.e. ```export const UserSchema = z.object({ ... })```.
+/// Schemas of nested entities are imported from their own schema file, so these need to be annotated with ```#[entity(zod)]``` as well.
+pub(crate) fn schema(input: &DeriveInput, options: &TypeOptions) -> syn::Result<TokenStream> {
+    if let Some(param) = input.generics.type_params().next() {
+        return Err(syn::Error::new_spanned(param, "Zod schemas can not be generated for generic entities"));
+    }
+
+    let mut entities = vec![];
+    let schema = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => object(&data.fields, &input.attrs, options, &mut entities)?,
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => zod(&TsType::from_type(&fields.unnamed[0].ty, options), &mut entities),
+            Fields::Unnamed(fields) => {
+                let elements = fields.unnamed.iter().filter(|field| !entity::is_omitted(&field.attrs)).map(|field| zod(&TsType::from_type(&field.ty, options), &mut entities)).collect::<Vec<_>>();
+                quote! { format!("z.tuple([{}])", [#(#elements),*].join(", ")) }
+            }
+            Fields::Unit => quote! { "z.null()".to_owned() },
+        },
+        Data::Enum(_) => match entity::variant_names(input)? {
+            Some(names) => {
+                let variants = names.iter().map(|name| format!("{name:?}")).collect::<Vec<_>>().join(", ");
+                let schema = format!("z.enum([{variants}])");
+                quote! { #schema.to_owned() }
+            }
+            None => return Err(syn::Error::new_spanned(&input.ident, "Zod schemas can only be generated for structs and enums without data")),
+        },
+        Data::Union(_) => return Err(syn::Error::new_spanned(&input.ident, "Zod schemas can not be generated for unions")),
+    };
+
+    let header = config::header();
+    let import = "import { z } from \"zod\"";
+
+    Ok(quote! {{
+        let mut entities: Vec<String> = vec![#(<#entities as tauri_bindgen_ts::ts_rs::TS>::name()),*];
+        entities.sort();
+        entities.dedup();
+        let imports = entities.iter().map(|name| format!("\nimport {{ {name}Schema }} from \"./{name}.zod\"")).collect::<String>();
+
+        format!("{}\n{}{}\n\nexport const {}Schema = {}\n", #header, #import, imports, Entity::name(), #schema)
+    }})
+}
+
+/// Schema of a struct with named fields, using the same field names as serde
+fn object(fields: &Fields, attrs: &[Attribute], options: &TypeOptions, entities: &mut Vec<Type>) -> syn::Result<TokenStream> {
+    let rule = serde_attr::value(attrs, "rename_all");
+    let mut properties = vec![];
+    let mut flattened = vec![];
+    for field in fields.iter().filter(|field| !entity::is_omitted(&field.attrs)) {
+        // Flattened fields are part of the object itself
+        if serde_attr::flag(&field.attrs, "flatten") {
+            let schema = zod(&TsType::from_type(&field.ty, options), entities);
+            flattened.push(quote! { format!(".and({})", #schema) });
+            continue;
+        }
+
+        let name = field_name(field, rule.as_ref())?;
+        let schema = zod(&TsType::from_type(&field.ty, options), entities);
+        // Fields that are skipped when serializing may be missing
+        let optional = if serde_attr::value(&field.attrs, "skip_serializing_if").is_some() { ".optional()" } else { "" };
+        properties.push(quote! { format!("{}: {}{}", #name, #schema, #optional) });
+    }
+
+    Ok(quote! { format!("z.object({{ {} }}){}", [#(#properties),*].join(", "), Vec::<String>::from([#(#flattened),*]).concat()) })
+}
+
+/// Object keys that are not valid identifiers need to be quoted
+fn field_name(field: &Field, rule: Option<&LitStr>) -> syn::Result<String> {
+    let name = entity::field_name(field, rule)?;
+    let is_identifier = !name.starts_with(|c: char| c.is_ascii_digit()) && !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+
+    Ok(if is_identifier { name } else { format!("{name:?}") })
+}
+
+/// Expands to an expression that evaluates to the Zod schema of the given type as `String`
+fn zod(ty: &TsType, entities: &mut Vec<Type>) -> TokenStream {
+    match ty {
+        TsType::Builtin("void" | "null") => quote! { "z.null()".to_owned() },
+        TsType::Builtin(DURATION) => quote! { "z.object({ secs: z.number(), nanos: z.number() })".to_owned() },
+        TsType::Builtin("Uint8Array") => quote! { "z.instanceof(Uint8Array)".to_owned() },
+        TsType::Builtin(NONZERO_BRAND) => quote! { "z.number().int().refine((value) => value !== 0)".to_owned() },
+        TsType::Builtin(name) => {
+            let schema = format!("z.{name}()");
+            quote! { #schema.to_owned() }
+        }
+        // Generic entities do not have a schema, channels are only passed to commands
+        TsType::Custom(_) | TsType::Generic(..) | TsType::Channel(_) => quote! { "z.unknown()".to_owned() },
+        TsType::Entity(ty) => {
+            entities.push(*ty.clone());
+            quote! { format!("{}Schema", <#ty as tauri_bindgen_ts::ts_rs::TS>::name()) }
+        }
+        TsType::Nullable(inner) => {
+            let inner = zod(inner, entities);
+            quote! { format!("{}.nullable()", #inner) }
+        }
+        TsType::Array(inner) => {
+            let inner = zod(inner, entities);
+            quote! { format!("z.array({})", #inner) }
+        }
+        TsType::FixedArray(inner, len) => {
+            let inner = zod(inner, entities);
+            quote! { format!("z.array({}).length({})", #inner, #len) }
+        }
+        TsType::Tuple(elems) => {
+            let elems = elems.iter().map(|elem| zod(elem, entities)).collect::<Vec<_>>();
+            quote! { format!("z.tuple([{}])", [#(#elems),*].join(", ")) }
+        }
+        TsType::Union(elems) => {
+            let elems = elems.iter().map(|elem| zod(elem, entities)).collect::<Vec<_>>();
+            quote! { format!("z.union([{}])", [#(#elems),*].join(", ")) }
+        }
+        TsType::ReadonlyArray(inner) => {
+            let inner = zod(inner, entities);
+            quote! { format!("z.array({}).readonly()", #inner) }
+        }
+        TsType::Record(key, value) => {
+            let key = zod(key, entities);
+            let value = zod(value, entities);
+            quote! { format!("z.record({}, {})", #key, #value) }
+        }
+    }
+}
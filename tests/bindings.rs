@@ -0,0 +1,272 @@
+//! Generates the bindings of the fixture app in `tests/fixtures/app` and checks that these are valid TypeScript.
+//! The type check requires `tsc` and is therefore only run with `cargo test -- --ignored`, its path can be set with the environment variable `TSC`.
+
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/app")
+}
+
+/// Runs the tests of the fixture crate, which write the bindings to `src-gen`
+fn generate(app: &Path) -> Vec<PathBuf> {
+    let out_dir = app.join("src-gen");
+    if out_dir.exists() {
+        fs::remove_dir_all(&out_dir).expect("Failed to remove previously generated bindings");
+    }
+
+    assert!(cargo_test(app, &[]), "Tests of the fixture app failed");
+
+    let mut files = vec![];
+    collect(&out_dir, &mut files);
+    files.sort();
+    files
+}
+
+/// Runs the tests of the fixture crate with the given arguments for the test binary, returning whether they passed
+fn cargo_test(app: &Path, args: &[&str]) -> bool {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+    Command::new(cargo)
+        .arg("test")
+        .arg("--quiet")
+        .arg("--manifest-path")
+        .arg(app.join("src-tauri/Cargo.toml"))
+        .arg("--")
+        .args(args)
+        .env("CARGO_TARGET_DIR", Path::new(env!("CARGO_TARGET_TMPDIR")).join("fixtures"))
+        .status()
+        .expect("Failed to run cargo")
+        .success()
+}
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).expect("Failed to read generated bindings") {
+        let path = entry.expect("Failed to read generated bindings").path();
+        if path.is_dir() {
+            collect(&path, files);
+        } else if path.extension().is_some_and(|extension| extension == "ts") {
+            files.push(path);
+        }
+    }
+}
+
+/// Paths of the generated bindings relative to `src-gen`, the bindings are only generated once for all tests
+fn generated() -> &'static [String] {
+    static GENERATED: OnceLock<Vec<String>> = OnceLock::new();
+    GENERATED.get_or_init(|| {
+        let app = fixture();
+        generate(&app).iter().map(|file| file.strip_prefix(app.join("src-gen")).unwrap().to_string_lossy().replace('\\', "/")).collect()
+    })
+}
+
+#[test]
+fn bindings_are_generated_for_all_items() {
+    let generated = generated();
+    for expected in ["index.ts", "User.ts", "UserId.ts", "Role.ts", "Shape.ts", "Users.ts", "Point.ts", "settings/Settings.ts", "duration.ts", "get_user.ts", "onUserUpdated.ts", "shapes.ts", "counter_value.ts", "reset_counter.ts"] {
+        assert!(generated.iter().any(|file| file == expected), "Missing {expected} in the generated bindings: {generated:?}");
+    }
+}
+
+/// Content of a generated binding, given its path relative to `src-gen`
+fn binding(file: &str) -> String {
+    assert!(generated().iter().any(|generated| generated == file), "Missing {file} in the generated bindings");
+    fs::read_to_string(fixture().join("src-gen").join(file)).expect("Failed to read the generated binding")
+}
+
+#[test]
+fn sequences_of_entities_are_arrays_that_import_the_entity() {
+    let binding = binding("list_users.ts");
+
+    assert!(binding.contains("import type { User } from \"./User\""), "{binding}");
+    assert!(binding.contains("Promise<User[]>"), "{binding}");
+}
+
+#[test]
+fn optional_sequences_are_nullable_arrays() {
+    assert!(binding("tagged_users.ts").contains("tagged_users(tags: (string | null)[] | null): Promise<User[] | null>"));
+}
+
+#[test]
+fn tuple_structs_are_type_aliases() {
+    assert!(binding("Score.ts").contains("export type Score = number;"));
+    assert!(binding("Point.ts").contains("export type Point = [number, number];"));
+}
+
+#[test]
+fn borrowed_arguments_are_declared_as_the_owned_type() {
+    assert!(binding("greet.ts").contains("greet(name: string): Promise<string>"));
+    assert!(binding("join_words.ts").contains("join_words(separator: string, words: string[]): Promise<string>"));
+}
+
+#[test]
+fn option_fields_are_nullable_or_optional() {
+    assert!(binding("Address.ts").contains("unit: string | null"));
+    assert!(binding("Contact.ts").contains("middle_name?: string"));
+}
+
+#[test]
+fn generic_entities_keep_their_type_parameters() {
+    assert!(binding("Page.ts").contains("export interface Page<T> { items: Array<T>, total: number, }"));
+
+    let binding = binding("user_page.ts");
+    assert!(binding.contains("user_page(page: Page<UserId>): Promise<Page<User>>"), "{binding}");
+    for entity in ["Page", "User", "UserId"] {
+        assert!(binding.contains(&format!("import type {{ {entity} }} from \"./{entity}\"")), "{binding}");
+    }
+}
+
+#[test]
+fn partial_types_declare_all_fields_as_optional() {
+    assert!(binding("Address.ts").contains("export type PartialAddress = { [K in keyof Address]?: Address[K] };"));
+    assert!(binding("Page.ts").contains("export type PartialPage<T> = { [K in keyof Page<T>]?: Page<T>[K] };"));
+    assert!(binding("index.ts").contains("export * from \"./Address\""));
+}
+
+#[test]
+fn const_enums_declare_the_serialized_values() {
+    let binding = binding("Direction.ts");
+
+    assert!(binding.contains("export const enum Direction { North = \"north\", South = \"south\" }"), "{binding}");
+    assert!(binding.contains("export type DirectionValue = `${Direction}`;"), "{binding}");
+}
+
+#[test]
+fn post_processed_bindings_have_the_header_of_this_crate() {
+    assert!(binding("Direction.ts").starts_with("// This file was generated by [tauri-bindgen-ts]"));
+    assert!(binding("Score.ts").starts_with("// This file was generated by [ts-rs]"));
+}
+
+#[test]
+fn results_without_data_resolve_with_void_and_document_their_error() {
+    let binding = binding("update_settings.ts");
+
+    assert!(binding.contains("update_settings(settings: Settings): Promise<void> { return await invoke<void>('update_settings', { settings }) }"), "{binding}");
+    assert!(binding.contains("@throws {string}"), "{binding}");
+}
+
+#[test]
+fn bindings_name_the_location_of_their_item() {
+    let source = fs::read_to_string(fixture().join("src-tauri/src/lib.rs")).expect("Failed to read the fixture app");
+    for (file, item) in [("User.ts", "pub struct User {"), ("greet.ts", "pub fn greet("), ("onUserUpdated.ts", "pub struct UserUpdated")] {
+        let binding = binding(file);
+        let line = binding.lines().find_map(|line| line.strip_prefix("// source: src/lib.rs:")).unwrap_or_else(|| panic!("Missing source in {file}: {binding}"));
+        let line = line.parse::<usize>().expect("Source should end with a line number");
+
+        assert!(source.lines().nth(line - 1).is_some_and(|line| line.contains(item)), "{file} points to line {line} instead of {item}");
+        assert!(binding.contains("\n// generated at: "), "{binding}");
+    }
+}
+
+#[test]
+fn renamed_commands_invoke_the_generated_wrapper() {
+    assert!(binding("userName.ts").contains("export async function userName(id: number): Promise<string> { return await invoke<string>('userName', { id }) }"));
+    assert!(binding("incrementCounter.ts").contains("export async function incrementCounter(by: number): Promise<number> { return await invoke<number>('increment_counter', { by }) }"));
+}
+
+#[test]
+fn imported_tauri_types_are_injected() {
+    assert!(binding("app_name.ts").contains("export async function app_name(): Promise<string> { return await invoke<string>('app_name', {  }) }"));
+}
+
+#[test]
+fn binary_data_is_converted_from_and_into_arrays_of_numbers() {
+    assert!(binding("compress.ts").contains("export async function compress(data: Uint8Array, dictionary: Uint8Array | null): Promise<Uint8Array> { return await invoke<number[]>('compress', { data: Array.from(data), dictionary: dictionary && Array.from(dictionary) }).then((bytes) => new Uint8Array(bytes)) }"));
+}
+
+#[test]
+fn async_commands_resolve_with_their_output() {
+    assert!(binding("refresh.ts").contains("refresh(): Promise<void>"));
+    assert!(binding("reset_counter.ts").contains("reset_counter(to: number): Promise<number>"));
+}
+
+#[test]
+fn debounced_commands_have_a_debounced_variant() {
+    let binding = binding("search_users.ts");
+
+    assert!(binding.contains("export async function search_users(query: string): Promise<User[]>"), "{binding}");
+    assert!(binding.contains("export function search_usersDebounced(query: string): Promise<User[]>"), "{binding}");
+    assert!(binding.contains("search_users(query).then("), "{binding}");
+}
+
+#[test]
+fn readme_lists_commands_and_entities() {
+    generated();
+    let readme = fs::read_to_string(fixture().join("src-gen/BINDINGS.md")).expect("Failed to read the overview of the bindings");
+
+    assert!(readme.contains("### `greet`\n\n```ts\ngreet(name: string): Promise<string>\n```"), "{readme}");
+    assert!(readme.contains("```ts\ninterface Page<T> { items: Array<T>, total: number, }\n```"), "{readme}");
+    assert!(readme.find("## Commands") < readme.find("## Entities"), "{readme}");
+}
+
+#[test]
+fn permissions_are_documented_and_collected() {
+    assert!(binding("ping.ts").contains(" * @permission core:app:default\n * @permission core:window:allow-close\n"));
+
+    let permissions = fs::read_to_string(fixture().join("src-gen/permissions.json")).expect("Failed to read the permissions");
+    assert_eq!(permissions, "{\n  \"permissions\": [\n    \"core:app:default\",\n    \"core:window:allow-close\"\n  ]\n}\n");
+}
+
+#[test]
+fn nested_collections_are_resolved() {
+    let binding = binding("group_users.ts");
+
+    assert!(binding.contains("group_users(groups: Record<string, User | null>[]): Promise<Record<string, User[]>>"), "{binding}");
+    assert!(binding.contains("import type { User } from \"./User\""), "{binding}");
+}
+
+#[test]
+fn factories_use_the_default_of_the_entity_or_of_its_fields() {
+    assert!(binding("Address.ts").contains("export function createAddress(fields: Partial<Address> = {}): Address { return { ...{ \"street\": \"\", \"unit\": null }, ...fields } }"));
+    assert!(binding("Preferences.ts").contains("return { ...{\"theme\":\"light\",\"font_size\":14}, ...fields }"));
+
+    let binding = binding("Profile.ts");
+    assert!(binding.contains("import { createAddress } from \"./Address\""), "{binding}");
+    assert!(binding.contains("{ \"address\": createAddress(), \"preferences\": createPreferences(), \"scores\": [0, 0] }"), "{binding}");
+}
+
+#[test]
+fn return_types_can_be_declared_as_union() {
+    let binding = binding("lookup.ts");
+
+    assert!(binding.contains("lookup(key: string): Promise<User | Settings>"), "{binding}");
+    assert!(binding.contains("Resolves with the user or the settings") && !binding.contains("Registers all commands"), "{binding}");
+    assert!(binding.contains("import type { User } from \"./User\""), "{binding}");
+    assert!(binding.contains("import type { Settings } from \"./settings/Settings\""), "{binding}");
+}
+
+#[test]
+fn generated_bindings_are_up_to_date() {
+    generated();
+
+    assert!(cargo_test(&fixture(), &["--ignored", "bindings_are_up_to_date"]), "The generated bindings differ from those that are generated again");
+}
+
+#[test]
+fn btree_maps_are_records_same_as_hash_maps() {
+    assert!(binding("Inventory.ts").contains("export interface Inventory { counts: Record<string, number>, sorted_counts: Record<string, number>, }"));
+    assert!(binding("Inventory.zod.ts").contains("counts: z.record(z.string(), z.number()), sorted_counts: z.record(z.string(), z.number())"));
+}
+
+#[test]
+fn tuple_variants_are_tuple_types() {
+    assert!(binding("Payload.ts").contains("{ type: \"Point\", value: [number, number] } | { type: \"Named\", value: { x: number, y: number, } }"));
+    assert!(binding("Movement.ts").contains("{ Moved: [number, number] } | \"Idle\""));
+}
+
+#[test]
+#[ignore = "requires tsc, run with `cargo test -- --ignored`"]
+fn generated_bindings_are_valid_typescript() {
+    generated();
+
+    let tsc = env::var("TSC").unwrap_or_else(|_| "tsc".to_owned());
+    let status = match Command::new(&tsc).arg("--noEmit").arg("-p").arg(fixture().join("tsconfig.json")).status() {
+        Ok(status) => status,
+        Err(err) if err.kind() == ErrorKind::NotFound => panic!("`{tsc}` was not found, install TypeScript or set its path with the environment variable TSC"),
+        Err(err) => panic!("Failed to run {tsc}: {err}"),
+    };
+    assert!(status.success(), "The generated bindings do not type-check");
+}
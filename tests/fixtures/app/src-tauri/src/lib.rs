@@ -0,0 +1,296 @@
+//! Exercises the bindings of a typical Tauri app, which are then type-checked with `tsc` by the integration tests
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use tauri_bindgen_ts::prelude::*;
+
+/// A user of the app
+#[entity(guard)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    pub id: UserId,
+    pub display_name: String,
+    pub role: Role,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    pub tags: Vec<String>,
+}
+
+#[entity(branded, guard)]
+pub struct UserId(pub u32);
+
+#[entity(guard)]
+pub enum Role { Admin, Member, Guest }
+
+#[entity]
+#[serde(tag = "type", content = "value")]
+pub enum Payload {
+    Point(f64, f64),
+    Named { x: f64, y: f64 },
+}
+
+#[entity]
+pub enum Movement {
+    Moved(i32, i32),
+    Idle,
+}
+
+#[entity(const_enum)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction { North, South }
+
+#[entity]
+#[serde(tag = "type")]
+pub enum Shape {
+    Circle { radius: f64 },
+    Rect { width: f64, height: f64 },
+}
+
+// Commands import entities from the export directory, so those in a module need to specify the path
+#[entity(module = "settings", import_from = "./settings/Settings")]
+pub struct Settings {
+    pub timeout: Duration,
+    pub limits: HashMap<String, u32>,
+    pub max_users: std::num::NonZeroU32,
+}
+
+#[entity]
+pub type Users = Vec<User>;
+
+#[entity]
+pub struct Point(pub i32, pub i32);
+
+#[entity]
+pub struct Score(pub u32);
+
+#[entity(partial)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u32,
+}
+
+#[entity(optional_fields)]
+pub struct Contact {
+    pub name: String,
+    pub middle_name: Option<String>,
+}
+
+#[entity(guard, zod)]
+pub struct Inventory {
+    pub counts: HashMap<String, u32>,
+    pub sorted_counts: BTreeMap<String, u32>,
+}
+
+#[entity(factory, partial)]
+pub struct Address {
+    pub street: String,
+    pub unit: Option<String>,
+}
+
+#[entity(factory)]
+pub struct Preferences {
+    pub theme: String,
+    pub font_size: u32,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences { theme: "light".to_owned(), font_size: 14 }
+    }
+}
+
+#[entity(factory)]
+pub struct Profile {
+    pub address: Address,
+    pub preferences: Preferences,
+    pub scores: [u8; 2],
+}
+
+/// Returns the user with the given id
+#[command]
+pub fn get_user(id: UserId) -> Option<User> {
+    let _ = id;
+    None
+}
+
+#[command]
+pub fn list_users(role: Option<Role>) -> Vec<User> {
+    let _ = role;
+    vec![]
+}
+
+#[command]
+pub fn tagged_users(tags: Option<Vec<Option<String>>>) -> Option<Vec<User>> {
+    let _ = tags;
+    None
+}
+
+#[command]
+pub fn user_page(page: Page<UserId>) -> Page<User> {
+    Page { items: vec![], total: page.total }
+}
+
+#[command]
+pub fn update_settings(settings: Settings) -> Result<(), String> {
+    let _ = settings;
+    Ok(())
+}
+
+#[command]
+pub fn area(shape: Shape) -> f64 {
+    match shape {
+        Shape::Circle { radius } => radius * radius * std::f64::consts::PI,
+        Shape::Rect { width, height } => width * height,
+    }
+}
+
+#[command(acl = ["core:app:default", "core:window:allow-close"])]
+pub fn ping() {}
+
+#[command]
+pub fn join_words(separator: Cow<'_, str>, words: Cow<'_, [String]>) -> Cow<'static, str> {
+    Cow::Owned(words.join(&separator))
+}
+
+#[command]
+pub async fn refresh() {}
+
+#[command(bytes_as = "Uint8Array")]
+pub fn compress(data: Vec<u8>, dictionary: Option<Vec<u8>>) -> Vec<u8> {
+    let _ = dictionary;
+    data
+}
+
+/// Stands in for `use tauri::AppHandle`, as the stub of Tauri only provides its macros
+mod runtime {
+    pub struct AppHandle;
+}
+use runtime::AppHandle;
+
+#[command]
+pub fn app_name(app: AppHandle) -> String {
+    let _ = app;
+    "fixture".to_owned()
+}
+
+#[command(position = "after")]
+#[allow(clippy::needless_pass_by_value)]
+pub fn greet(name: &str) -> String {
+    format!("Hello, {name}!")
+}
+
+#[command]
+pub fn group_users(groups: Vec<HashMap<String, Option<User>>>) -> HashMap<String, Vec<User>> {
+    let _ = groups;
+    HashMap::new()
+}
+
+#[command(rename = "userName")]
+pub fn user_name(mut id: u32) -> String {
+    id += 1;
+    format!("User {id}")
+}
+
+#[command(debounce_ms = 300)]
+pub fn search_users(query: String) -> Vec<User> {
+    let _ = query;
+    vec![]
+}
+
+#[entity]
+#[tauri_event("user-updated")]
+pub struct UserUpdated {
+    pub id: u32,
+}
+
+#[command_module(name = "shapes")]
+pub mod shapes {
+    use tauri_bindgen_ts::prelude::*;
+
+    use super::Point;
+
+    #[command]
+    pub fn origin() -> Point {
+        Point(0, 0)
+    }
+}
+
+pub struct Counter;
+
+#[command_group]
+impl Counter {
+    pub fn counter_value() -> u32 {
+        0
+    }
+
+    pub async fn reset_counter(to: u32) -> u32 {
+        to
+    }
+
+    #[command(invoke_name = "increment_counter", ts_name = "incrementCounter")]
+    pub fn increment(by: u32) -> u32 {
+        by
+    }
+}
+
+/// Resolves with the user or the settings, depending on what the key refers to
+#[command(return_type = "User | Settings")]
+pub fn lookup(key: String) -> Result<serde_json::Value, String> {
+    let _ = key;
+    Ok(serde_json::Value::Null)
+}
+
+/// Registers all commands, as the app would with the invoke handler
+#[allow(dead_code)]
+fn handlers() {
+    tauri::generate_handler![get_user, list_users, tagged_users, user_page, update_settings, area, ping, refresh, compress, app_name, greet, join_words, group_users, search_users, userName, lookup, counter_value, reset_counter, increment_counter];
+}
+
+/// Only passes once the bindings have been generated, which `tests/bindings.rs` does before running it
+#[cfg(test)]
+#[test]
+#[ignore = "requires the generated bindings"]
+fn bindings_are_up_to_date() {
+    tauri_bindgen_ts::verify::assert_bindings_up_to_date("../src-gen");
+}
+
+/// Checks that the declared types match what serde actually serializes
+#[cfg(test)]
+mod serialized {
+    use super::*;
+
+    #[test]
+    fn optional_fields_are_omitted_when_none() {
+        let contact = Contact { name: "Ada".to_owned(), middle_name: None };
+        assert_eq!(serde_json::to_string(&contact).unwrap(), r#"{"name":"Ada"}"#);
+        assert!(serde_json::from_str::<Contact>(r#"{"name":"Ada"}"#).unwrap().middle_name.is_none());
+    }
+
+    #[test]
+    fn const_enums_are_serialized_as_their_values() {
+        assert_eq!(serde_json::to_string(&Direction::North).unwrap(), r#""north""#);
+        assert!(matches!(serde_json::from_str(r#""south""#).unwrap(), Direction::South));
+    }
+
+    #[test]
+    fn tuple_variants_are_serialized_as_arrays() {
+        assert_eq!(serde_json::to_string(&Payload::Point(1.5, 2.0)).unwrap(), r#"{"type":"Point","value":[1.5,2.0]}"#);
+        assert_eq!(serde_json::to_string(&Payload::Named { x: 1.5, y: 2.0 }).unwrap(), r#"{"type":"Named","value":{"x":1.5,"y":2.0}}"#);
+        assert_eq!(serde_json::to_string(&Movement::Moved(1, 2)).unwrap(), r#"{"Moved":[1,2]}"#);
+        assert_eq!(serde_json::to_string(&Movement::Idle).unwrap(), r#""Idle""#);
+    }
+
+    #[test]
+    fn option_fields_are_null_when_none() {
+        let address = Address { street: "Main Street".to_owned(), unit: None };
+        assert_eq!(serde_json::to_string(&address).unwrap(), r#"{"street":"Main Street","unit":null}"#);
+    }
+
+    #[test]
+    fn maps_are_serialized_as_objects() {
+        let inventory = Inventory { counts: HashMap::from([("a".to_owned(), 1)]), sorted_counts: BTreeMap::from([("b".to_owned(), 2), ("a".to_owned(), 1)]) };
+        assert_eq!(serde_json::to_string(&inventory).unwrap(), r#"{"counts":{"a":1},"sorted_counts":{"a":1,"b":2}}"#);
+    }
+}
@@ -0,0 +1 @@
+export function invoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T>
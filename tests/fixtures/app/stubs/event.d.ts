@@ -0,0 +1,3 @@
+export type UnlistenFn = () => void
+export interface Event<T> { event: string, id: number, payload: T }
+export function listen<T>(event: string, handler: (event: Event<T>) => void): Promise<UnlistenFn>
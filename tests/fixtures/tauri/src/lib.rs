@@ -0,0 +1,42 @@
+use proc_macro::{TokenStream, TokenTree};
+
+/// Keys accepted by the real attribute
+const KEYS: [&str; 3] = ["rename_all", "root", "async"];
+
+/// Leaves the command unchanged, as the fixtures are never invoked from a frontend.
+/// Same as the real attribute, a macro for `generate_handler!` is declared next to the function, which is not allowed inside impl blocks,
+/// and keys other than those of the real attribute are rejected.
+#[proc_macro_attribute]
+pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut expects_key = true;
+    for token in attr {
+        match token {
+            TokenTree::Punct(punct) if punct.as_char() == ',' => expects_key = true,
+            TokenTree::Ident(key) if expects_key && !KEYS.contains(&key.to_string().as_str()) => {
+                return format!("compile_error!(\"Unknown key `{key}`, expected one of: {}\");", KEYS.join(", ")).parse().expect("Error should be valid");
+            }
+            _ => expects_key = false,
+        }
+    }
+
+    let tokens = item.clone().into_iter().collect::<Vec<_>>();
+    let name = tokens.windows(2).find_map(|pair| match pair {
+        [TokenTree::Ident(keyword), TokenTree::Ident(name)] if keyword.to_string() == "fn" => Some(name.to_string()),
+        _ => None,
+    });
+    let Some(name) = name else { return item };
+
+    let wrapper: TokenStream = format!("#[doc(hidden)] #[allow(unused_macros)] macro_rules! __cmd__{name} {{ () => {{}}; }}").parse().expect("Wrapper should be valid");
+    item.into_iter().chain(wrapper).collect()
+}
+
+/// Same as the real macro, commands are referred to by the name of the function, so that their wrapper macro is found
+#[proc_macro]
+pub fn generate_handler(input: TokenStream) -> TokenStream {
+    let calls = input.into_iter().filter_map(|token| match token {
+        TokenTree::Ident(name) => Some(format!("__cmd__{name}!();")),
+        _ => None,
+    }).collect::<String>();
+
+    format!("{{ {calls} }}").parse().expect("Handler should be valid")
+}